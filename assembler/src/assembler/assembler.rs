@@ -1,4 +1,5 @@
 use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::JsValue;
 
 use std::collections::HashMap;
 use core::hint::unreachable_unchecked;
@@ -17,7 +18,15 @@ use crate::lang::LoggerMessage;
 #[wasm_bindgen]
 pub struct Assembler {
     //hashmaps dont deallocate all the memory after deleting items, keeping it as a member can save a few os calls
-    identifiers: HashMap<String, u16>
+    identifiers: HashMap<String, u16>,
+
+    // source line (starting at 1) and address of every instruction of the last successful assembly
+    instruction_lines: Vec<u32>,
+    instruction_addresses: Vec<u16>,
+
+    // name and address of every label of the last successful assembly
+    label_names: Vec<String>,
+    label_addresses: Vec<u16>,
 }
 
 //public api
@@ -27,10 +36,46 @@ impl Assembler {
     pub fn new() -> Assembler {
         Assembler {
             identifiers: HashMap::new(),
+
+            instruction_lines: Vec::new(),
+            instruction_addresses: Vec::new(),
+
+            label_names: Vec::new(),
+            label_addresses: Vec::new(),
         }
     }
 
+    /// Source lines (starting at 1) of the instructions of the last successful assembly, in the
+    /// same order as `Self::instruction_addresses`.
+    pub fn instruction_lines(&self) -> Vec<u32> {
+        self.instruction_lines.clone()
+    }
+
+    /// Addresses of the instructions of the last successful assembly, to compare them with the
+    /// ones executed (code coverage).
+    pub fn instruction_addresses(&self) -> Vec<u16> {
+        self.instruction_addresses.clone()
+    }
+
+    /// Names of the labels of the last successful assembly, in the same order as
+    /// `Self::label_addresses`. Constants aren't included.
+    pub fn label_names(&self) -> Vec<JsValue> {
+        self.label_names.iter().map(|name| JsValue::from_str(name)).collect()
+    }
+
+    /// Addresses of the labels of the last successful assembly, to name the subroutines in the
+    /// profiles of the emulator.
+    pub fn label_addresses(&self) -> Vec<u16> {
+        self.label_addresses.clone()
+    }
+
     pub fn assemble(&mut self, lines: &str, rom: &mut [u8], rom_start: u16) -> bool {
+        self.instruction_lines.clear();
+        self.instruction_addresses.clear();
+
+        self.label_names.clear();
+        self.label_addresses.clear();
+
         let mut interface = AssemblerInterface::new(
             rom,
             &mut self.identifiers,
@@ -116,6 +161,11 @@ impl Assembler {
             for (num, item) in stage_3 {
                 Logger::set_current_line(num + 1);
 
+                if item.is_instruction() {
+                    self.instruction_lines.push(num as u32 + 1);
+                    self.instruction_addresses.push(interface.rom_start().wrapping_add(interface.write_ptr()));
+                }
+
                 if let Err(e) = item.execute(&mut interface) {
                     if !Logger::msg_handled() {
                         Logger::err_msg(e.to_logger_msg());
@@ -134,6 +184,10 @@ impl Assembler {
 
             Self::clear_unused_rom(&mut interface);
 
+            let (names, addresses) = interface.take_code_labels().into_iter().unzip();
+            self.label_names = names;
+            self.label_addresses = addresses;
+
             self.identifiers.clear();
 
             true
@@ -142,6 +196,9 @@ impl Assembler {
 
             self.identifiers.clear();
 
+            self.instruction_lines.clear();
+            self.instruction_addresses.clear();
+
             false
         }
     }
@@ -6,6 +6,11 @@ pub trait CodeItemTrait {
 
     fn process(&self, asm: &mut AssemblerInterface) -> (bool, bool);
     fn execute(&self, asm: &mut AssemblerInterface) -> ParseResult<()>;
+
+    /// True for the items that write code the cpu can execute (not data).
+    fn is_instruction(&self) -> bool {
+        false
+    }
 }
 
 pub fn to_boxed<T>(what: T) -> Box<dyn CodeItemTrait> where T: CodeItemTrait + 'static {
@@ -76,6 +76,10 @@ impl CodeItemTrait for Instruction {
         (true, true)
     }
 
+    fn is_instruction(&self) -> bool {
+        true
+    }
+
     fn execute(&self, asm: &mut AssemblerInterface) -> ParseResult<()> {
         asm.write(self.opcode);
 
@@ -49,7 +49,7 @@ impl CodeItemTrait for Label {
 
             ok = false;
         } else {
-            asm.insert_label(
+            asm.insert_code_label(
                 self.name.as_str(),
                 asm.rom_start() + asm.write_ptr(),
             );
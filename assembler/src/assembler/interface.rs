@@ -5,6 +5,9 @@ pub struct AssemblerInterface<'a> {
     write_ptr: u16,
     map: &'a mut HashMap<String, u16>,
 
+    // labels of the code, the other identifiers are constants
+    code_labels: Vec<(String, u16)>,
+
     rom_start: u16,
 }
 
@@ -15,6 +18,8 @@ impl<'a> AssemblerInterface<'a> {
             write_ptr: 0,
             map,
 
+            code_labels: Vec::new(),
+
             rom_start,
         }
     }
@@ -38,6 +43,16 @@ impl<'a> AssemblerInterface<'a> {
         self.map.insert(String::from(k), v);
     }
 
+    pub fn insert_code_label(&mut self, k: &str, v: u16) {
+        self.insert_label(k, v);
+
+        self.code_labels.push((String::from(k), v));
+    }
+
+    pub fn take_code_labels(&mut self) -> Vec<(String, u16)> {
+        std::mem::take(&mut self.code_labels)
+    }
+
     pub fn write_ptr(&self) -> u16 {
         self.write_ptr
     }
@@ -8,3 +8,4 @@ mod js_logger;
 
 mod assembler;
 mod parser;
+mod output;
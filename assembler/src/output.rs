@@ -0,0 +1,105 @@
+use wasm_bindgen::prelude::wasm_bindgen;
+
+// Writers for the assembled ROM, to exchange it with EEPROM programmers and other toolchains.
+// The text formats are returned as ASCII bytes, with a record per line.
+
+const BYTES_PER_RECORD: usize = 16;
+
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Copy, Clone)]
+pub enum OutputFormat {
+    // the bytes as they are
+    Raw = 0,
+
+    // ":LLAAAATT...CC" records, ending with an end of file record
+    IntelHex = 1,
+
+    // Motorola S1 records, with an S0 header, an S5 count and an S9 with the start address
+    SRecord = 2,
+
+    // Commodore style, the load address (little endian) followed by the data
+    Prg = 3,
+}
+
+/// Encodes `rom`, which is loaded at `rom_start`, as `format`.
+#[wasm_bindgen]
+pub fn encode_output(format: OutputFormat, rom: &[u8], rom_start: u16) -> Vec<u8> {
+    match format {
+        OutputFormat::Raw => rom.to_vec(),
+        OutputFormat::IntelHex => intel_hex(rom, rom_start).into_bytes(),
+        OutputFormat::SRecord => srecord(rom, rom_start).into_bytes(),
+        OutputFormat::Prg => prg(rom, rom_start),
+    }
+}
+
+fn prg(rom: &[u8], rom_start: u16) -> Vec<u8> {
+    let mut out = rom_start.to_le_bytes().to_vec();
+    out.extend_from_slice(rom);
+
+    out
+}
+
+fn hex_record(out: &mut String, bytes: &[u8]) {
+    for b in bytes {
+        out.push_str(&format!("{:02X}", b));
+    }
+
+    out.push('\n');
+}
+
+fn intel_hex(rom: &[u8], rom_start: u16) -> String {
+    let mut out = String::new();
+
+    for (i, chunk) in rom.chunks(BYTES_PER_RECORD).enumerate() {
+        let addr = rom_start.wrapping_add((i * BYTES_PER_RECORD) as u16);
+
+        let mut record = vec![chunk.len() as u8];
+        record.extend_from_slice(&addr.to_be_bytes());
+        record.push(0x00); //data
+        record.extend_from_slice(chunk);
+
+        let sum = record.iter().fold(0_u8, |sum, b| sum.wrapping_add(*b));
+        record.push(sum.wrapping_neg());
+
+        out.push(':');
+        hex_record(&mut out, &record);
+    }
+
+    out.push_str(":00000001FF\n");
+
+    out
+}
+
+fn srecord_line(out: &mut String, record_type: u8, addr: u16, data: &[u8]) {
+    // byte count includes the address and the checksum
+    let mut record = vec![(data.len() + 3) as u8];
+    record.extend_from_slice(&addr.to_be_bytes());
+    record.extend_from_slice(data);
+
+    let sum = record.iter().fold(0_u8, |sum, b| sum.wrapping_add(*b));
+    record.push(!sum);
+
+    out.push_str(&format!("S{}", record_type));
+    hex_record(out, &record);
+}
+
+fn srecord(rom: &[u8], rom_start: u16) -> String {
+    let mut out = String::new();
+
+    srecord_line(&mut out, 0, 0, b"cr6502");
+
+    let mut records = 0_u16;
+
+    for (i, chunk) in rom.chunks(BYTES_PER_RECORD).enumerate() {
+        let addr = rom_start.wrapping_add((i * BYTES_PER_RECORD) as u16);
+
+        srecord_line(&mut out, 1, addr, chunk);
+        records += 1;
+    }
+
+    srecord_line(&mut out, 5, records, &[]);
+    srecord_line(&mut out, 9, rom_start, &[]);
+
+    out
+}
@@ -80,7 +80,8 @@ export class DeviceRepresentation {
     }
 
 
-    setupWidget(memArrayBuilder) {
+    // memory copies the device contents, see the setupDeviceWidgetByIndexAndRepr action.
+    setupWidget(memory) {
         console.warn("Unimplemented setup");
     }
 
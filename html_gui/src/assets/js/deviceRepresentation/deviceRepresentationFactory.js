@@ -7,18 +7,30 @@ import(process.env.VUE_APP_SYS_JS_PATH).then(sysLib => {
     const RepresentedCpu = require("./representedCpu").RepresentedCpu;
     const RepresentedPixelScreen = require("./representedPixelScreen").RepresentedPixelScreen;
     const RepresentedAsciiBuffer = require("./representedAsciiBuffer").RepresentedAsciiBuffer;
+    const RepresentedCharLcd = require("./representedCharLcd").RepresentedCharLcd;
+    const RepresentedColorScreen = require("./representedColorScreen").RepresentedColorScreen;
     const RepresentedRom = require("./representedRom").RepresentedRom;
     const RepresentedRam = require("./representedRam").RepresentedRam;
+    const RepresentedRegisters = require("./representedRegisters").RepresentedRegisters;
 
     MappedDevIdsToTypes = {
         [DeviceId.CPU]: RepresentedCpu,
 
         [DeviceId.PixelScreen]: RepresentedPixelScreen,
         [DeviceId.AsciiIOBuffer]: RepresentedAsciiBuffer,
+        [DeviceId.CharLcd]: RepresentedCharLcd,
+        [DeviceId.ColorScreen]: RepresentedColorScreen,
 
         [DeviceId.Rom]: RepresentedRom,
         [DeviceId.Ram]: RepresentedRam,
     }
+
+    // the enum also maps the values to their names
+    for (let [name, type] of Object.entries(DeviceId)) {
+        if (typeof type === "number" && !MappedDevIdsToTypes[type]) {
+            MappedDevIdsToTypes[type] = RepresentedRegisters.forType(type, name);
+        }
+    }
 });
 
 export class DeviceRepresentationFactory {
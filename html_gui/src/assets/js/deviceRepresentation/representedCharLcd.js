@@ -0,0 +1,53 @@
+import {DeviceRepresentation} from "./deviceRepresentation";
+import EnvironmentWidgetCharLcd from "../../../components/EnvironmentWidgetCharLcd";
+
+const DeviceId = require(process.env.VUE_APP_SYS_JS_PATH).DeviceId;
+
+export class RepresentedCharLcd extends DeviceRepresentation {
+    constructor(start, end, uid) {
+        super(start, end, uid);
+
+        this.widget.displayData.columns = 0;
+        this.widget.displayData.text = [];
+        this.widget.displayData.cursor = -1;
+        this.widget.displayData.blink = false;
+        this.widget.displayData.displayOn = false;
+    }
+
+
+    static get type() {
+        return DeviceId.CharLcd;
+    }
+
+    static get widgetComponent() {
+        return EnvironmentWidgetCharLcd;
+    }
+
+    static get niceName() {
+        return "Character LCD";
+    }
+
+    static get hasFixedSize() {
+        return true;
+    }
+
+    static get needsExplicitUpdates() {
+        return true;
+    }
+
+
+    setupWidget() {
+        this.widget.displayData.columns = this.updatePkg.get("columns");
+
+        this.updateWidget();
+    }
+
+    updateWidget() {
+        if (this.updatePkg.get("update")) {
+            this.widget.displayData.text = this.updatePkg.get("text");
+            this.widget.displayData.cursor = this.updatePkg.get("cursor");
+            this.widget.displayData.blink = this.updatePkg.get("blink");
+            this.widget.displayData.displayOn = this.updatePkg.get("displayOn");
+        }
+    }
+}
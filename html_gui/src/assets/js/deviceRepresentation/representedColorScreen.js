@@ -0,0 +1,14 @@
+import {RepresentedPixelScreen} from "./representedPixelScreen";
+
+const DeviceId = require(process.env.VUE_APP_SYS_JS_PATH).DeviceId;
+
+// Same widget as the pixel screen, only the resolution and the palette change.
+export class RepresentedColorScreen extends RepresentedPixelScreen {
+    static get type() {
+        return DeviceId.ColorScreen;
+    }
+
+    static get niceName() {
+        return "Color Screen (32x32)";
+    }
+}
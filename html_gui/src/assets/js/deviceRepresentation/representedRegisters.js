@@ -0,0 +1,31 @@
+import {RepresentedRom} from "./representedRom";
+
+// Devices without their own representation, their registers are shown in a memory monitor.
+export class RepresentedRegisters extends RepresentedRom {
+    constructor(start, end, uid) {
+        super(start, end, uid);
+    }
+
+
+    static get niceName() {
+        return "Device";
+    }
+
+    static get hasFixedSize() {
+        return true;
+    }
+
+
+    // Returns a class for the devices with the DeviceId type, named as the DeviceId.
+    static forType(type, name) {
+        return class extends RepresentedRegisters {
+            static get type() {
+                return type;
+            }
+
+            static get niceName() {
+                return name;
+            }
+        };
+    }
+}
@@ -8,6 +8,11 @@ export class RepresentedRom extends DeviceRepresentation {
         super(start, end, uid);
 
         this.widget.displayData.memArray = null;
+
+        // accesses of every address (for the heatmap), null while they aren't counted
+        this.widget.displayData.accessCounts = null;
+
+        this._memory = null;
     }
 
 
@@ -32,12 +37,48 @@ export class RepresentedRom extends DeviceRepresentation {
     }
 
 
-    setupWidget(memArrayBuilder) {
-        this.widget.displayData.memArray = memArrayBuilder();
+    setupWidget(memory) {
+        this._memory = memory;
+
+        // the snapshot already has every page, so the pages dirty until now are discarded
+        this.widget.displayData.memArray = memory.snapshot();
+        memory.takeDirtyPages();
+
+        this.widget.displayData.accessCounts = memory.accessCounts() || null;
+    }
+
+    // {pc, value, cycle} of the last write of the cpu to the address at offset, null if it wasn't
+    // written while recording them.
+    lastWrite(offset) {
+        let record = this._memory.lastWrite(offset);
+
+        if (record === undefined) {
+            return null;
+        }
+
+        let lastWrite = {pc: record.pc, value: record.value, cycle: record.cycle};
+        record.free();
+
+        return lastWrite;
     }
 
-    updateWidget(memArrayBuilder) {
-        if (this.updatePkg.get("update")) {
+    updateWidget() {
+        let memArray = this.widget.displayData.memArray;
+        let pageSize = this._memory.pageSize;
+
+        // only the pages written since the last update are copied
+        let dirtyPages = this._memory.takeDirtyPages();
+
+        for (let page of dirtyPages) {
+            memArray.set(this._memory.copyRange(page * pageSize, pageSize), page * pageSize);
+        }
+
+        let accessCounts = this._memory.accessCounts() || null;
+        let countsChanged = accessCounts !== null || this.widget.displayData.accessCounts !== null;
+
+        this.widget.displayData.accessCounts = accessCounts;
+
+        if (dirtyPages.length || countsChanged) {
             // Vue doesnt support typed arrays for reactivity, so we need to force an update.
             this.widget.displayData.__ob__.dep.notify();
         }
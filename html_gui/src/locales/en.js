@@ -58,6 +58,25 @@ const LangEn = {
             debug: "Debug",
             debugCycle: "Execute Cycle",
             debugInstruction: "Execute Instruction",
+            debugStepOver: "Step Over (runs whole subroutine calls)",
+            debugStepOut: "Step Out (runs until the subroutine returns)",
+            debugRunTo: "Run To Address",
+            debugRunToSubmit: "Run",
+            debugContinue: "Continue (runs until a breakpoint)",
+
+            breakpoints: {
+                title: "Breakpoints",
+
+                address: "Address",
+                condition: "Condition",
+                conditionPlaceholder: "Always, or an expression like A == $FF && mem[$10] > 3",
+                hitCount: "Hit count",
+                logMessage: "Log message",
+                logMessagePlaceholder: "Stops, or logs a message like X = {'{'}X:x{'}'} and continues",
+
+                emptyMessage: "There are no breakpoints",
+                submit: "Set breakpoint",
+            },
 
             settings: "Settings",
         },
@@ -81,6 +100,12 @@ const LangEn = {
                 downloadPrj: "Download Project",
                 deletePrj: "Delete Project",
 
+                machineProfile: {
+                    title: "Machine profile",
+                    explanation: "Replaces every device with the ones of a well known machine, the program is kept.",
+                    load: "Load profile",
+                },
+
                 deletePrompt: {
                     nameTitle: "Delete project:",
                     idTitle: "Id:"
@@ -148,6 +173,23 @@ const LangEn = {
                     },
                 },
 
+                editPrompt: {
+                    title: "Move or resize device",
+
+                    failedToEdit: "Failed to change the device.",
+
+                    dev: {
+                        device: "Device",
+
+                        select: "Select One",
+                    },
+
+                    button: {
+                        toggle: "Move/Resize",
+                        submit: "Apply",
+                    },
+                },
+
                 buildRom: {
                     title: "Build Rom",
 
@@ -168,6 +210,44 @@ const LangEn = {
                 },
 
                 operationsPerCycle: "Operations per cycle",
+
+                profiler: {
+                    explanation: "The profiler counts the executions and cycles of every address and subroutine while the program runs, the counts are kept until cleared.",
+                    enabled: "Profile the program",
+                    clear: "Clear counts",
+                    downloadReport: "Download report",
+                    downloadFoldedStacks: "Download flamegraph stacks",
+                },
+
+                accessCounters: {
+                    explanation: "Counting the reads, writes and executions of every address shades the memory monitors by how much each address is used, and grays out the lines of the program that never ran.",
+                    enabled: "Count memory accesses",
+                    clear: "Clear counts",
+                },
+
+                writeProvenance: {
+                    explanation: "Recording which instruction wrote every address shows it when hovering a value in the memory monitors, to find out who left a wrong value there.",
+                    enabled: "Record memory writes",
+                    clear: "Forget writes",
+                },
+
+                haltConditions: {
+                    explanation: "Stop the program when it reaches one of these instructions, programs that wait for interrupts in a loop to itself need the first one disabled.",
+                    jumpToSelf: "Jump or branch to itself",
+                    brk: "BRK",
+                    kil: "Opcodes that jam the CPU (KIL)",
+                },
+
+                ramInit: {
+                    explanation: "Real RAM powers on with garbage, not zeros. Filling it with a pattern or random bytes on every reset, and warning about the reads of addresses the program never wrote, finds the variables that are used before being set.",
+                    kind: "RAM contents after a reset",
+                    zero: "Zeros",
+                    pattern: "Fixed byte",
+                    random: "Random bytes",
+                    value: "Byte",
+                    seed: "Seed",
+                    uninitReads: "Warn about reads of uninitialized RAM",
+                },
             },
         },
 
@@ -201,10 +281,27 @@ const LangEn = {
             },
 
             memMonitor: {
-                cappedMsg: "-- Capped by Settings --"
+                cappedMsg: "-- Capped by Settings --",
+
+                lastWrite: "Written {value} by the instruction at {pc}, cycle {cycle}",
+                neverWritten: "Not written by the program",
             },
         },
 
+        runStop: {
+            jumpToSelf: "The program stopped at a jump to itself, A = {code}",
+            brk: "The program stopped at a BRK, A = {code}",
+            kil: "The cpu jammed on a KIL opcode, A = {code}",
+            exitPort: "The program exited with code {code}",
+            cycleLimit: "The step didn't finish after {code} cycles",
+            backtrace: "Call stack: {code}",
+            breakpoint: "The program stopped at the breakpoint at {code}",
+            logpoint: "{code}",
+            uninitRead: "Read of uninitialized RAM at {code} by the instruction at {code2}",
+            semihostOutput: "Output: {code}",
+            rdyWait: "The cpu is waiting for RDY, the instruction isn't finished (PC = {code})",
+        },
+
         logbar: {
             title: "Status Log",
 
@@ -58,6 +58,25 @@ const LangEs = {
             debug: "Debuguear",
             debugCycle: "Ejecutar ciclo",
             debugInstruction: "Ejecutar instruccion",
+            debugStepOver: "Saltar subrutina (ejecuta las llamadas completas)",
+            debugStepOut: "Salir de subrutina (ejecuta hasta que retorne)",
+            debugRunTo: "Ejecutar hasta direccion",
+            debugRunToSubmit: "Ejecutar",
+            debugContinue: "Continuar (ejecuta hasta un breakpoint)",
+
+            breakpoints: {
+                title: "Breakpoints",
+
+                address: "Direccion",
+                condition: "Condicion",
+                conditionPlaceholder: "Siempre, o una expresion como A == $FF && mem[$10] > 3",
+                hitCount: "Numero de pasadas",
+                logMessage: "Mensaje",
+                logMessagePlaceholder: "Se detiene, o escribe un mensaje como X = {'{'}X:x{'}'} y continua",
+
+                emptyMessage: "No hay breakpoints",
+                submit: "Poner breakpoint",
+            },
 
             settings: "Opciones",
         },
@@ -81,6 +100,12 @@ const LangEs = {
                 downloadPrj: "Descargar",
                 deletePrj: "Eliminar",
 
+                machineProfile: {
+                    title: "Perfil de máquina",
+                    explanation: "Reemplaza todos los dispositivos por los de una máquina conocida, el programa se mantiene.",
+                    load: "Cargar perfil",
+                },
+
                 deletePrompt: {
                     nameTitle: "Eliminar proyecto:",
                     idTitle: "Id:",
@@ -148,6 +173,23 @@ const LangEs = {
                     }
                 },
 
+                editPrompt: {
+                    title: "Mover o redimensionar dispositivo",
+
+                    failedToEdit: "Ocurrio un error.",
+
+                    dev: {
+                        device: "Dispositivo",
+
+                        select: "Seleccionar ...",
+                    },
+
+                    button: {
+                        toggle: "Mover/Redimensionar",
+                        submit: "Aplicar",
+                    }
+                },
+
                 buildRom: {
                     title: "Rom de programa",
 
@@ -168,6 +210,44 @@ const LangEs = {
                 },
 
                 operationsPerCycle: "Instrucciones por ciclo",
+
+                profiler: {
+                    explanation: "El perfilador cuenta las ejecuciones y ciclos de cada direccion y subrutina mientras el programa se ejecuta, los conteos se mantienen hasta borrarlos.",
+                    enabled: "Perfilar el programa",
+                    clear: "Borrar conteos",
+                    downloadReport: "Descargar reporte",
+                    downloadFoldedStacks: "Descargar pilas para flamegraph",
+                },
+
+                accessCounters: {
+                    explanation: "Contar las lecturas, escrituras y ejecuciones de cada direccion colorea los monitores de memoria segun cuanto se usa cada direccion, y atenua las lineas del programa que nunca se ejecutaron.",
+                    enabled: "Contar accesos a memoria",
+                    clear: "Borrar conteos",
+                },
+
+                writeProvenance: {
+                    explanation: "Registrar que instruccion escribio cada direccion lo muestra al pasar el raton sobre un valor de los monitores de memoria, para saber quien dejo ahi un valor incorrecto.",
+                    enabled: "Registrar escrituras en memoria",
+                    clear: "Olvidar escrituras",
+                },
+
+                haltConditions: {
+                    explanation: "Detiene el programa cuando llega a una de estas instrucciones, los programas que esperan interrupciones en un bucle sobre sí mismo necesitan la primera desactivada.",
+                    jumpToSelf: "Salto o bifurcación a sí mismo",
+                    brk: "BRK",
+                    kil: "Opcodes que bloquean la CPU (KIL)",
+                },
+
+                ramInit: {
+                    explanation: "La RAM real arranca con basura, no con ceros. Llenarla con un patron o bytes aleatorios en cada reinicio, y avisar de las lecturas de direcciones que el programa nunca escribio, encuentra las variables que se usan antes de darles valor.",
+                    kind: "Contenido de la RAM tras reiniciar",
+                    zero: "Ceros",
+                    pattern: "Byte fijo",
+                    random: "Bytes aleatorios",
+                    value: "Byte",
+                    seed: "Semilla",
+                    uninitReads: "Avisar de lecturas de RAM sin inicializar",
+                },
             },
         },
 
@@ -201,10 +281,27 @@ const LangEs = {
             },
 
             memMonitor: {
-                cappedMsg: "-- Limitado en Opciones --"
+                cappedMsg: "-- Limitado en Opciones --",
+
+                lastWrite: "Escrito {value} por la instruccion en {pc}, ciclo {cycle}",
+                neverWritten: "No escrito por el programa",
             },
         },
 
+        runStop: {
+            jumpToSelf: "El programa se detuvo en un salto a si mismo, A = {code}",
+            brk: "El programa se detuvo en un BRK, A = {code}",
+            kil: "La cpu se bloqueo en un opcode KIL, A = {code}",
+            exitPort: "El programa termino con el codigo {code}",
+            cycleLimit: "El paso no termino despues de {code} ciclos",
+            backtrace: "Pila de llamadas: {code}",
+            breakpoint: "El programa se detuvo en el breakpoint de {code}",
+            logpoint: "{code}",
+            uninitRead: "Lectura de RAM sin inicializar en {code} por la instruccion en {code2}",
+            semihostOutput: "Salida: {code}",
+            rdyWait: "La cpu espera a RDY, la instruccion no ha terminado (PC = {code})",
+        },
+
         logbar: {
             title: "Mensajes",
 
@@ -39,7 +39,13 @@ import {
     /*faCog, faTimesCircle*/
 
     //EnvironmentActionBar
-    faHammer, faSyncAlt, faPlay, faStop, faBug, faChevronRight, faCog,
+    faHammer, faSyncAlt, faPlay, faStop, faBug, faChevronRight, faCog, faShare, faLevelUpAlt, faForward,
+
+    //EnvironmentActionbarRunTo
+    faMapMarkerAlt,
+
+    //EnvironmentActionbarBreakpoints
+    faDotCircle, /*faTrashAlt*/
 
     //EnvironmentLogBar
     /*faChevronRight,*/ faGhost, faChevronDown,
@@ -48,7 +54,7 @@ import {
     faTrashAlt, faDownload,
 
     //EnvironmentSettingPrjDevices
-    /*faTrashAlt, faPlus,*/ faRandom,
+    /*faTrashAlt, faPlus,*/ faRandom, faEdit,
 
     //EnvironmentWidgetAsciiIOBuffer
     faSortAmountDownAlt,
@@ -66,10 +72,12 @@ library.add(
     faBars,
     faPlus,
     faCog, faTimesCircle,
-    faHammer, faSyncAlt, faPlay, faStop, faBug, faChevronRight, faCog,
+    faHammer, faSyncAlt, faPlay, faStop, faBug, faChevronRight, faCog, faShare, faLevelUpAlt, faForward,
+    faMapMarkerAlt,
+    faDotCircle,
     faChevronRight, faGhost, faChevronDown,
     faTrashAlt, faDownload,
-    faRandom,
+    faRandom, faEdit,
     faSortAmountDownAlt,
 );
 
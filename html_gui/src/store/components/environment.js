@@ -24,6 +24,32 @@ loop:
     JMP %loop
 `;
 
+// Used to convert the run mode operations into the cycles given to System.run
+const AverageCyclesPerOperation = 4;
+
+// Cycles a debugger step (over, out or to an address) can run before giving up, so an endless
+// loop doesn't freeze the page.
+const DebugStepMaxCycles = 1000000;
+
+// environment.runStop messages for every sysLib.StopReason that stops the program early.
+const StopReasonTemplates = {
+    [sysLib.StopReason.CycleLimit]: "cycleLimit",
+    [sysLib.StopReason.JumpToSelf]: "jumpToSelf",
+    [sysLib.StopReason.Brk]: "brk",
+    [sysLib.StopReason.Kil]: "kil",
+    [sysLib.StopReason.ExitPort]: "exitPort",
+    [sysLib.StopReason.Breakpoint]: "breakpoint",
+};
+
+// Addresses and values as they are shown in the messages, like $0200.
+function hexText(value, digits) {
+    return "$" + value.toString(16).toUpperCase().padStart(digits, "0");
+}
+
+function pcText(context) {
+    return hexText(context.getters.__system.cpu_register(sysLib.CpuRegister.Pc), 4);
+}
+
 const EnvironmentInitialState = {
     lock: {
         build: false,
@@ -44,6 +70,42 @@ const EnvironmentInitialState = {
 
     runModeInterval: null,
 
+    profilerEnabled: false,
+
+    accessCountersEnabled: false,
+
+    writeProvenanceEnabled: false,
+
+    // what the RAM contains after a reset, value is the byte of the pattern or the random seed
+    ramInit: {
+        kind: sysLib.RamInitKind.Zero,
+        value: 0,
+    },
+
+    uninitReadsEnabled: false,
+
+    // enabled sysLib.HaltConditions, the same as the defaults of the system
+    haltConditions: {
+        [sysLib.HaltCondition.JumpToSelf]: false,
+        [sysLib.HaltCondition.Brk]: false,
+        [sysLib.HaltCondition.Kil]: true,
+    },
+
+    // lines (starting at 1) and addresses of the instructions of the last successful build
+    programInstructions: {
+        lines: [],
+        addresses: [],
+    },
+
+    // names of the labels of the last build by address, to name the subroutines of the profiles
+    programLabels: new Map(),
+
+    // lines of the last build that were never executed, while the accesses are counted
+    deadCodeLines: [],
+
+    // {address, condition, hitCount, logMessage} of every breakpoint set in the system, by address
+    breakpoints: [],
+
     errorMessage: null,
 
     meta: {},
@@ -81,6 +143,57 @@ export const EnvironmentStore = {
             state.status.currentAction = value;
         },
 
+        __setProfilerEnabled(state, value) {
+            state.profilerEnabled = value;
+        },
+
+        __setAccessCountersEnabled(state, value) {
+            state.accessCountersEnabled = value;
+        },
+
+        __setWriteProvenanceEnabled(state, value) {
+            state.writeProvenanceEnabled = value;
+        },
+
+        __setRamInit(state, {kind, value}) {
+            state.ramInit.kind = kind;
+            state.ramInit.value = value;
+        },
+
+        __setUninitReadsEnabled(state, value) {
+            state.uninitReadsEnabled = value;
+        },
+
+        __setHaltCondition(state, {condition, enabled}) {
+            state.haltConditions[condition] = enabled;
+        },
+
+        __setProgramInstructions(state, {lines, addresses}) {
+            state.programInstructions.lines = lines;
+            state.programInstructions.addresses = addresses;
+        },
+
+        __setProgramLabels(state, {names, addresses}) {
+            let labels = new Map();
+
+            // the first label of an address names it, the next ones are usually loops inside it
+            names.forEach((name, i) => {
+                if (!labels.has(addresses[i])) {
+                    labels.set(addresses[i], name);
+                }
+            });
+
+            state.programLabels = labels;
+        },
+
+        __setDeadCodeLines(state, value) {
+            state.deadCodeLines = value;
+        },
+
+        __setBreakpoints(state, value) {
+            state.breakpoints = value;
+        },
+
 
         addMessage(state, value) {
             state.messages.push(value);
@@ -121,6 +234,10 @@ export const EnvironmentStore = {
             state.devices.splice(index, 1);
         },
 
+        replaceDeviceInCache(state, [index, device]) {
+            state.devices.splice(index, 1, device);
+        },
+
         swapDevicesIndex(state, [a, b]) {
             let devices = state.devices;
 
@@ -240,22 +357,51 @@ export const EnvironmentStore = {
             return exportObj;
         },
 
+        // Throws the error of the first device that can't be added.
         async loadProjectUnchecked(context, prj) {
             context.commit("currentStatus", EnvironmentState.INITIALIZING);
 
             context.state.meta = prj.meta;
             context.state.settings = prj.settings;
 
-            await Promise.all(prj.devices.map(dev => {
-                    context.dispatch("addDeviceWithWidget", dev);
-                })
-            );
+            await Promise.all(prj.devices.map(dev => context.dispatch("addDeviceWithWidget", dev)));
 
             await context.dispatch("updateWasmRomAddress");
 
             context.commit("currentStatus", EnvironmentState.IDLE);
         },
 
+        // files is a Map from the file names used in the configuration to Uint8Arrays.
+        // Throws the error returned by rust if the configuration is invalid.
+        async loadMachineConfig(context, {text, files = new Map()}) {
+            await context.dispatch("resetToCleanState", false);
+
+            try {
+                context.getters.__system.load_config(text, files);
+            } finally {
+                await context.dispatch("purgeAndReloadDeviceCache");
+
+                context.commit("currentStatus", EnvironmentState.IDLE);
+            }
+        },
+
+        // name is one of the System.profile_names()
+        async loadMachineProfile(context, name) {
+            await context.dispatch("resetToCleanState", false);
+
+            try {
+                context.getters.__system.load_profile(name);
+            } finally {
+                await context.dispatch("purgeAndReloadDeviceCache");
+
+                context.commit("currentStatus", EnvironmentState.IDLE);
+            }
+        },
+
+        exportMachineConfig(context) {
+            return context.getters.__system.export_config();
+        },
+
 
         buildToRom(context) {
             context.commit("resetMessages");
@@ -264,18 +410,52 @@ export const EnvironmentStore = {
             const sys = context.getters.__system;
 
             let romIndex = context.getters.targetProgramRomIndex;
-            let ptr = sys.device_data_ptr_by_index(romIndex);
-            let {size, start} = context.state.devices[romIndex];
+            let {start, end} = context.state.devices[romIndex];
 
             let program = document.querySelector("#editor").innerText;
-            let romData = new Uint8Array(sys.memory.buffer, ptr, size);
+            let romData = sys.dump(start, end);
 
             let success = asm.assemble(program, romData, start);
 
+            if (success) {
+                sys.load(start, romData);
+
+                // the counts of the previous program don't mean anything for the new one
+                sys.access_counters_clear();
+            }
+
+            context.commit("__setProgramInstructions", {
+                lines: Array.from(asm.instruction_lines()),
+                addresses: asm.instruction_addresses(),
+            });
+
+            context.commit("__setProgramLabels", {
+                names: asm.label_names(),
+                addresses: Array.from(asm.label_addresses()),
+            });
+
             context.dispatch("updateAllDevicesWidgets");
             context.commit("buildStatus", success);
         },
 
+        // format is a sysLib.ImageFormat, address and skip are only used by raw images.
+        // Throws the error returned by rust if the image is invalid.
+        loadImage(context, {format, data, address = 0, skip = 0}) {
+            context.getters.__system.load_image(format, data, address, skip);
+
+            context.dispatch("updateAllDevicesWidgets");
+        },
+
+        // format is an asmLib.OutputFormat, returns the encoded program rom.
+        exportProgramRom(context, format) {
+            let romIndex = context.getters.targetProgramRomIndex;
+            let {start, end} = context.state.devices[romIndex];
+
+            let romData = context.getters.__system.dump(start, end);
+
+            return asmLib.encode_output(format, romData, start);
+        },
+
         updateProgramRomId(context, id) {
             context.state.settings.targetProgramRomId = id;
 
@@ -293,6 +473,24 @@ export const EnvironmentStore = {
             context.getters.__system.set_initial_pc(initialAddress);
         },
 
+        // register is a sysLib.CpuRegister, returns false if the cpu is in the middle of an instruction.
+        setCpuRegister(context, {register, value}) {
+            let changed = context.getters.__system.cpu_set_register(register, value);
+
+            context.dispatch("updateDeviceWidgetByIndex", 0);
+
+            return changed;
+        },
+
+        // flag is a sysLib.CpuFlag, returns false if the cpu is in the middle of an instruction.
+        setCpuFlag(context, {flag, value}) {
+            let changed = context.getters.__system.cpu_set_flag(flag, value);
+
+            context.dispatch("updateDeviceWidgetByIndex", 0);
+
+            return changed;
+        },
+
         resetSystem(context) {
             context.getters.__system.reset_system();
 
@@ -310,9 +508,35 @@ export const EnvironmentStore = {
                 let operationsPerCycle = context.getters.projectSettings.runModeOperationsPerCycle;
 
                 context.state.runModeInterval = setInterval(_ => {
-                    context.getters.__system.execute_operation_x(operationsPerCycle);
+                    let result = context.getters.__system.run(operationsPerCycle * AverageCyclesPerOperation);
+                    let reason = result.reason;
+                    let exitCode = result.exit_code;
+
+                    result.free();
 
                     context.dispatch("updateAllDevicesWidgets");
+                    context.dispatch("addBreakpointLogMessages");
+                    context.dispatch("addUninitReadMessages");
+                    context.dispatch("addSemihostOutputMessages");
+            context.dispatch("addSemihostOutputMessages");
+
+                    if (reason !== sysLib.StopReason.CycleLimit) {
+                        context.dispatch("toggleRun");
+
+                        let codeItem = reason === sysLib.StopReason.Breakpoint ? pcText(context) : exitCode.toString();
+
+                        context.commit("addMessage", {
+                            type: reason === sysLib.StopReason.Kil ? "warn" : "info",
+                            title: "Run",
+                            templateId: `environment.runStop.${StopReasonTemplates[reason]}`,
+                            codeItems: [codeItem],
+                        });
+
+                        // the program crashed, show where it came from
+                        if (reason === sysLib.StopReason.Kil || reason === sysLib.StopReason.Brk) {
+                            context.dispatch("addBacktraceMessage");
+                        }
+                    }
                 }, 4);
 
                 context.commit("currentStatus", EnvironmentState.RUNNING);
@@ -333,12 +557,233 @@ export const EnvironmentStore = {
             context.getters.__system.tick();
 
             context.dispatch("updateAllDevicesWidgets");
+            context.dispatch("addUninitReadMessages");
+            context.dispatch("addSemihostOutputMessages");
         },
 
         systemExecuteOperation(context) {
-            context.getters.__system.execute_operation();
+            let finished = context.getters.__system.execute_operation();
+
+            if (!finished) {
+                context.commit("addMessage", {
+                    type: "info",
+                    title: "Run",
+                    templateId: "environment.runStop.rdyWait",
+                    codeItems: [pcText(context)],
+                });
+            }
+
+            context.dispatch("updateAllDevicesWidgets");
+            context.dispatch("addUninitReadMessages");
+            context.dispatch("addSemihostOutputMessages");
+        },
+
+        systemStepOver(context) {
+            context.dispatch("__finishDebugStep", context.getters.__system.step_over(DebugStepMaxCycles));
+        },
+
+        systemStepOut(context) {
+            context.dispatch("__finishDebugStep", context.getters.__system.step_out(DebugStepMaxCycles));
+        },
+
+        systemRunTo(context, address) {
+            context.dispatch("__finishDebugStep", context.getters.__system.run_to(address, DebugStepMaxCycles));
+        },
+
+        // Runs until a breakpoint or halt condition.
+        systemContinue(context) {
+            context.dispatch("__finishDebugStep", context.getters.__system.run(DebugStepMaxCycles));
+        },
+
+        // Sets (or replaces) a breakpoint, an empty condition or log message means none. Returns the
+        // error message if the condition or log message has a bad expression.
+        setBreakpoint(context, {address, condition, hitCount, logMessage}) {
+            try {
+                context.getters.__system.set_breakpoint(address, condition, hitCount, logMessage);
+            } catch (error) {
+                return error.message;
+            }
+
+            let breakpoints = context.state.breakpoints.filter(breakpoint => breakpoint.address !== address);
+            breakpoints.push({address, condition, hitCount, logMessage});
+            breakpoints.sort((a, b) => a.address - b.address);
+
+            context.commit("__setBreakpoints", breakpoints);
+
+            return null;
+        },
+
+        removeBreakpoint(context, address) {
+            context.getters.__system.remove_breakpoint(address);
+
+            context.commit("__setBreakpoints", context.state.breakpoints.filter(breakpoint => breakpoint.address !== address));
+        },
+
+        // Shows the messages written by the logpoints since the last call.
+        addBreakpointLogMessages(context) {
+            for (let line of context.getters.__system.take_breakpoint_log()) {
+                context.commit("addMessage", {
+                    type: "info",
+                    title: "Log",
+                    templateId: "environment.runStop.logpoint",
+                    codeItems: [line],
+                });
+            }
+        },
+
+        setProfilerEnabled(context, enabled) {
+            context.getters.__system.set_profiler_enabled(enabled);
+
+            context.commit("__setProfilerEnabled", enabled);
+        },
+
+        clearProfiler(context) {
+            context.getters.__system.profiler_clear();
+        },
+
+        // Counts the accesses to every address, for the memory heatmaps and the code coverage.
+        setAccessCountersEnabled(context, enabled) {
+            context.getters.__system.set_access_counters_enabled(enabled);
+
+            context.commit("__setAccessCountersEnabled", enabled);
+
+            if (!enabled) {
+                context.commit("__setDeadCodeLines", []);
+            }
+
+            context.dispatch("updateAllDevicesWidgets");
+        },
+
+        clearAccessCounters(context) {
+            context.getters.__system.access_counters_clear();
+
+            context.dispatch("updateAllDevicesWidgets");
+        },
+
+        // Records which instruction wrote every address, shown when hovering the memory monitors.
+        setWriteProvenanceEnabled(context, enabled) {
+            context.getters.__system.set_write_provenance_enabled(enabled, 1);
+
+            context.commit("__setWriteProvenanceEnabled", enabled);
+        },
+
+        clearWriteProvenance(context) {
+            context.getters.__system.write_provenance_clear();
+        },
+
+        // kind is a sysLib.RamInitKind, it's used from the next reset.
+        setRamInit(context, {kind, value}) {
+            context.getters.__system.set_ram_init(kind, value);
+
+            context.commit("__setRamInit", {kind, value});
+        },
+
+        // Warns about the reads of RAM that wasn't written since the reset.
+        setUninitReadsEnabled(context, enabled) {
+            context.getters.__system.set_uninit_reads_enabled(enabled);
+
+            context.commit("__setUninitReadsEnabled", enabled);
+        },
+
+        // condition is a sysLib.HaltCondition, the run mode stops when it's found.
+        setHaltCondition(context, {condition, enabled}) {
+            context.getters.__system.set_halt_condition(condition, enabled);
+
+            context.commit("__setHaltCondition", {condition, enabled});
+        },
+
+        // Shows the reads of uninitialized RAM since the last call.
+        addUninitReadMessages(context) {
+            for (let read of context.getters.__system.take_uninit_reads()) {
+                context.commit("addMessage", {
+                    type: "warn",
+                    title: "Memory",
+                    templateId: "environment.runStop.uninitRead",
+                    codeItems: [hexText(read.addr, 4), hexText(read.pc, 4)],
+                });
+
+                read.free();
+            }
+        },
+
+        // Shows what the program printed through the semihosting devices.
+        addSemihostOutputMessages(context) {
+            let sys = context.getters.__system;
+
+            context.state.devices.forEach((device, index) => {
+                if (device.constructor.type !== sysLib.DeviceId.Semihost) {
+                    return;
+                }
+
+                let output = sys.semihost_take_output_by_index(index);
+
+                if (output && output.length > 0) {
+                    context.commit("addMessage", {
+                        type: "info",
+                        title: "Output",
+                        templateId: "environment.runStop.semihostOutput",
+                        codeItems: [new TextDecoder().decode(output)],
+                    });
+                }
+            });
+        },
+
+        // Returns the profile as text, or undefined if the profiler is disabled. The subroutines are
+        // named by the labels of the last build.
+        profilerReport(context) {
+            return context.getters.__system.profiler_report(context.state.programLabels);
+        },
+
+        // Returns the profile in the "folded stacks" format of flamegraph tools, or undefined if
+        // the profiler is disabled.
+        profilerFoldedStacks(context) {
+            return context.getters.__system.profiler_folded_stacks(context.state.programLabels);
+        },
+
+        addBacktraceMessage(context) {
+            let backtrace = context.getters.__system.call_stack_backtrace();
+
+            context.commit("addMessage", {
+                type: "info",
+                title: "Run",
+                templateId: "environment.runStop.backtrace",
+                codeItems: [backtrace.trim().split("\n").join(" ← ")],
+            });
+        },
+
+        // Frees the sysLib.RunResult of a debugger step, telling the user why it stopped if it
+        // didn't get where it was going.
+        __finishDebugStep(context, result) {
+            let reason = result.reason;
+            let exitCode = result.exit_code;
+
+            result.free();
 
             context.dispatch("updateAllDevicesWidgets");
+            context.dispatch("addBreakpointLogMessages");
+            context.dispatch("addUninitReadMessages");
+            context.dispatch("addSemihostOutputMessages");
+
+            if (reason !== sysLib.StopReason.TargetReached) {
+                let codeItem = exitCode.toString();
+
+                if (reason === sysLib.StopReason.CycleLimit) {
+                    codeItem = DebugStepMaxCycles.toString();
+                } else if (reason === sysLib.StopReason.Breakpoint) {
+                    codeItem = pcText(context);
+                }
+
+                context.commit("addMessage", {
+                    type: reason === sysLib.StopReason.Kil ? "warn" : "info",
+                    title: "Debug",
+                    templateId: `environment.runStop.${StopReasonTemplates[reason]}`,
+                    codeItems: [codeItem],
+                });
+
+                if (reason === sysLib.StopReason.Kil || reason === sysLib.StopReason.Brk) {
+                    context.dispatch("addBacktraceMessage");
+                }
+            }
         },
 
 
@@ -361,37 +806,52 @@ export const EnvironmentStore = {
         },
 
 
+        // Throws the SystemError returned by rust if the device can't be added.
         addDeviceWithWidget(context, {type, start, size, uid, config}) {
             let actualUid = uid || Tools.getRandomUID();
 
-            let success = context.getters.__system.add_device_with_uid(type, start, size, actualUid);
+            context.getters.__system.add_device_with_uid(type, start, size, actualUid);
 
-            if (success) {
-                let newDevIndex = context.state.devices.length; // assuming we are synchronized with rust
-
-                let newDev = context.getters.__system.device_representation_by_index(newDevIndex);
+            let newDevIndex = context.state.devices.length; // assuming we are synchronized with rust
 
-                if (config != null) {
-                    newDev.setWidgetConfig(config);
-                }
+            let newDev = context.getters.__system.device_representation_by_index(newDevIndex);
 
-                context.dispatch("setupDeviceWidgetByIndexAndRepr", {device: newDev, index: newDevIndex});
-                context.state.devices.push(newDev);
+            if (config != null) {
+                newDev.setWidgetConfig(config);
             }
 
-            return success;
+            context.dispatch("setupDeviceWidgetByIndexAndRepr", {device: newDev, index: newDevIndex});
+            context.state.devices.push(newDev);
         },
 
         setupDeviceWidgetByIndexAndRepr(context, {index, device}) {
             context.getters.__system.device_widget_setup_by_index(index, device.updatePkg);
 
-            let getMemFn = function () {
-                let ptr = context.getters.__system.device_data_ptr_by_index(index);
+            // The index changes when other devices are removed or swapped, so it's looked up on every call,
+            // the device might not be in the cache yet while it's being set up.
+            let currentIndex = function () {
+                let cachedIndex = context.state.devices.indexOf(device);
 
-                return new Uint8Array(context.getters.__system.memory.buffer, ptr, device.size);
+                return cachedIndex !== -1 ? cachedIndex : index;
             };
 
-            device.setupWidget(getMemFn);
+            let sys = context.getters.__system;
+
+            let memory = {
+                pageSize: sysLib.System.memory_page_size(),
+
+                snapshot: () => sys.device_snapshot_by_index(currentIndex()),
+                copyRange: (offset, len) => sys.device_copy_range_by_index(currentIndex(), offset, len),
+                takeDirtyPages: () => sys.device_take_dirty_pages_by_index(currentIndex()),
+
+                // undefined while the accesses aren't counted
+                accessCounts: () => sys.access_total_counts(device.start, device.size),
+
+                // a sysLib.WriteRecord (to be freed), undefined if it wasn't written while recording
+                lastWrite: offset => sys.last_write(device.start + offset),
+            };
+
+            device.setupWidget(memory);
         },
 
         updateDeviceWidgetByIndex(context, index) {
@@ -408,31 +868,61 @@ export const EnvironmentStore = {
             for (let i = 0; i < context.state.devices.length; i++) {
                 context.dispatch("updateDeviceWidgetByIndex", i);
             }
+
+            if (context.state.accessCountersEnabled) {
+                context.dispatch("updateCoverage");
+            }
+        },
+
+        updateCoverage(context) {
+            let {lines, addresses} = context.state.programInstructions;
+            let unexecuted = new Set(context.getters.__system.coverage_unexecuted(addresses));
+
+            let deadLines = lines.filter((line, i) => unexecuted.has(addresses[i]));
+
+            context.commit("__setDeadCodeLines", deadLines);
         },
 
         removeDeviceById(context, id) {
             let index = context.getters.deviceList.findIndex(dev => dev.uid === id);
 
-            if (index != null) {
-                let success = context.getters.__system.remove_device_by_index(index);
+            if (index !== -1) {
+                context.getters.__system.remove_device_by_index(index);
 
-                if (success) {
-                    context.commit("removeDeviceFromCacheByIndex", index);
-                }
+                context.commit("removeDeviceFromCacheByIndex", index);
             }
         },
 
-        swapDevicesByIndex(context, [a, b]) {
-            let success = context.getters.__system.swap_devices_by_index(a, b);
+        // Throws the SystemError returned by rust if the device can't be moved.
+        moveDeviceByIndex(context, {index, start}) {
+            context.getters.__system.move_device_by_index(index, start);
 
-            if (success) {
-                context.commit("swapDevicesIndex", [a, b]);
+            context.dispatch("reloadDeviceByIndex", index);
+        },
 
-                return true;
-            } else {
+        // Throws the SystemError returned by rust if the device can't be resized.
+        resizeDeviceByIndex(context, {index, size}) {
+            context.getters.__system.resize_device_by_index(index, size);
 
-                return false;
-            }
+            context.dispatch("reloadDeviceByIndex", index);
+        },
+
+        // Rebuilds the cached representation of a device changed in rust, keeping its widget config.
+        reloadDeviceByIndex(context, index) {
+            let oldDev = context.state.devices[index];
+            let newDev = context.getters.__system.device_representation_by_index(index);
+
+            newDev.setWidgetConfig(oldDev.widget.config);
+
+            context.dispatch("setupDeviceWidgetByIndexAndRepr", {device: newDev, index});
+            context.commit("replaceDeviceInCache", [index, newDev]);
+        },
+
+        // Throws the SystemError returned by rust if the devices can't be swapped.
+        swapDevicesByIndex(context, [a, b]) {
+            context.getters.__system.swap_devices_by_index(a, b);
+
+            context.commit("swapDevicesIndex", [a, b]);
         },
 
     },
@@ -451,6 +941,38 @@ export const EnvironmentStore = {
             return state.messages;
         },
 
+        profilerIsEnabled(state) {
+            return state.profilerEnabled;
+        },
+
+        accessCountersAreEnabled(state) {
+            return state.accessCountersEnabled;
+        },
+
+        writeProvenanceIsEnabled(state) {
+            return state.writeProvenanceEnabled;
+        },
+
+        ramInit(state) {
+            return state.ramInit;
+        },
+
+        uninitReadsAreEnabled(state) {
+            return state.uninitReadsEnabled;
+        },
+
+        haltConditions(state) {
+            return state.haltConditions;
+        },
+
+        deadCodeLines(state) {
+            return state.deadCodeLines;
+        },
+
+        breakpointList(state) {
+            return state.breakpoints;
+        },
+
 
         isInitializing(state) {
             return (
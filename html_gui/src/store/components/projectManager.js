@@ -220,9 +220,18 @@ export const ProjectManagerStore = {
 
                     await context.dispatch("env/resetToCleanState", null, {root: true});
 
-                    await context.dispatch("env/loadProjectUnchecked", prj, {root: true});
+                    try {
+                        await context.dispatch("env/loadProjectUnchecked", prj, {root: true});
 
-                    context.commit("env/setStatusIdle", null, {root: true});
+                        context.commit("env/setStatusIdle", null, {root: true});
+                    } catch (err) {
+                        let userMessage = `Failed to load project: ${err.message}`;
+
+                        console.error(userMessage, err);
+
+                        context.commit("env/setInitErrorMessage", userMessage, {root: true});
+                        context.commit("env/setStatusInitFailed", null, {root: true});
+                    }
 
                 } else {
                     let userMessage = "Failed to load project: Invalid schema.";
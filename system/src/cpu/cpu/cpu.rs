@@ -1,12 +1,12 @@
-use super::{CPUInterface, CPUOperationManager};
+use super::{CPUInterface, CPUOperationManager, OperationStart};
 use super::super::{
-    register::RegisterContainer,
+    register::{RegisterContainer, CpuRegister, CpuFlag},
     opcode::AddressingFn,
 };
 
 use crate::system::MemManager;
 
-use crate::dev::{DeviceTrait, DeviceId, utils};
+use crate::dev::{DeviceTrait, DeviceId, SignalLine, utils};
 
 use wasm_bindgen::JsValue;
 use js_sys::Map;
@@ -15,6 +15,10 @@ pub struct CPU {
     reg: RegisterContainer,
     initial_pc: u16,
 
+    /// If set, `initial_pc` is ignored and the pc is read from the reset vector.
+    use_reset_vector: bool,
+    reset_vector_pending: bool,
+
     opcode: CPUOperationManager,
 
     extra_cycle: Option<AddressingFn>,
@@ -29,6 +33,9 @@ impl CPU {
             reg: RegisterContainer::new(),
             initial_pc: 0,
 
+            use_reset_vector: false,
+            reset_vector_pending: false,
+
             opcode: CPUOperationManager::new(),
 
             extra_cycle: None,
@@ -38,6 +45,23 @@ impl CPU {
     }
 
     pub fn tick_with_mem(&mut self, mem_ref: &mut MemManager) {
+        if mem_ref.signals().is_asserted(SignalLine::Reset) {
+            self.reset_system();
+            return;
+        }
+
+        // RDY pulled low, the cpu waits
+        if mem_ref.signals().is_asserted(SignalLine::Rdy) {
+            return;
+        }
+
+        if self.reset_vector_pending {
+            self.reset_vector_pending = false;
+
+            self.read_reset_vector(mem_ref);
+            return;
+        }
+
         let mut inter = CPUInterface {
             mem: mem_ref,
 
@@ -59,8 +83,95 @@ impl CPU {
         self.opcode.operation_is_done(&self.extra_cycle)
     }
 
+    /// Returns true if the next tick starts a new instruction (or interrupt) at the pc.
+    pub fn is_between_instructions(&self) -> bool {
+        self.operation_is_done() && !self.reset_vector_pending
+    }
+
+    /// Returns (once) the instruction or interrupt started since the last call, and the pc it
+    /// started at.
+    pub fn take_started_operation(&mut self) -> Option<(OperationStart, u16)> {
+        self.opcode.take_started()
+    }
+
     pub fn set_initial_pc(&mut self, value: u16) {
         self.initial_pc = value;
+        self.use_reset_vector = false;
+    }
+
+    /// Makes the cpu start (after a reset) at the address in the reset vector ($FFFC).
+    pub fn set_initial_pc_from_reset_vector(&mut self) {
+        self.use_reset_vector = true;
+    }
+
+    /// Returns the address set with `Self::set_initial_pc`, or None if the reset vector is used.
+    pub fn initial_pc(&self) -> Option<u16> {
+        if self.use_reset_vector {
+            None
+        } else {
+            Some(self.initial_pc)
+        }
+    }
+
+    pub fn register(&self, reg: CpuRegister) -> u16 {
+        match reg {
+            CpuRegister::A => self.reg.a as u16,
+            CpuRegister::X => self.reg.x as u16,
+            CpuRegister::Y => self.reg.y as u16,
+            CpuRegister::P => self.reg.p as u16,
+            CpuRegister::S => self.reg.s as u16,
+            CpuRegister::Pc => self.reg.pc,
+        }
+    }
+
+    /// Changes a register, only between instructions, so the current one doesn't end with a mix
+    /// of old and new values.
+    ///
+    /// # Returns
+    /// Returns false (and does nothing) in the middle of an instruction, or if `value` doesn't fit
+    /// in the register.
+    pub fn set_register(&mut self, reg: CpuRegister, value: u16) -> bool {
+        if !self.operation_is_done() || (!reg.is_wide() && value > 0xFF) {
+            return false;
+        }
+
+        match reg {
+            CpuRegister::A => self.reg.a = value as u8,
+            CpuRegister::X => self.reg.x = value as u8,
+            CpuRegister::Y => self.reg.y = value as u8,
+            CpuRegister::P => self.reg.p = value as u8,
+            CpuRegister::S => self.reg.s = value as u8,
+
+            CpuRegister::Pc => {
+                self.reg.pc = value;
+
+                // the reset vector would overwrite it
+                self.reset_vector_pending = false;
+            }
+        }
+
+        true
+    }
+
+    pub fn flag(&self, flag: CpuFlag) -> bool {
+        (self.reg.p & flag.mask()) != 0
+    }
+
+    /// Sets or clears a flag of the P register, with the same rules as `Self::set_register`.
+    pub fn set_flag(&mut self, flag: CpuFlag, value: bool) -> bool {
+        let p = if value { self.reg.p | flag.mask() } else { self.reg.p & !flag.mask() };
+
+        self.set_register(CpuRegister::P, p as u16)
+    }
+
+    fn read_reset_vector(&mut self, mem_ref: &mut MemManager) {
+        mem_ref.set_addr(0xFFFC);
+        let pcl = mem_ref.read_at_addr();
+
+        mem_ref.set_addr(0xFFFD);
+        let pch = mem_ref.read_at_addr();
+
+        self.reg.pc = ((pch as u16) << 8) | pcl as u16;
     }
 }
 
@@ -79,6 +190,7 @@ impl DeviceTrait for CPU {
 
 
         self.reg.pc = self.initial_pc;
+        self.reset_vector_pending = self.use_reset_vector;
     }
 
     fn reset_hard(&mut self) {
@@ -6,3 +6,4 @@ mod operation_manager;
 pub use cpu::CPU;
 pub use interface::CPUInterface;
 use operation_manager::CPUOperationManager;
+pub use operation_manager::OperationStart;
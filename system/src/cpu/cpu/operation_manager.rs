@@ -1,12 +1,23 @@
 use super::CPUInterface;
 
 use crate::cpu::opcode::Decoder;
+use crate::dev::SignalLine;
 use super::super::opcode::{
     AddressingActions, AddressingFn,
     AnnotatedOpcode,
     addressing, operations,
 };
 
+// keep in sync with operations::FlagPositionOffset
+const FLAG_INTERRUPT: u8 = 1 << 2;
+
+/// What the cpu started executing at an instruction boundary.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum OperationStart {
+    Opcode(u8),
+    Irq,
+    Nmi,
+}
 
 pub struct CPUOperationManager {
     actions: &'static AddressingActions,
@@ -14,6 +25,9 @@ pub struct CPUOperationManager {
     op: AnnotatedOpcode,
 
     action_i: usize,
+
+    /// The last operation started and the pc it started at, until taken.
+    started: Option<(OperationStart, u16)>,
 }
 
 impl CPUOperationManager {
@@ -23,6 +37,8 @@ impl CPUOperationManager {
             op: operations::NOP,
 
             action_i: std::usize::MAX, //force a self.re_init()
+
+            started: None,
         }
     }
 
@@ -38,15 +54,47 @@ impl CPUOperationManager {
             inter.reg.pc
         );
 
-        let opcode = inter.mem.read_at_addr();
+        let opcode = inter.mem.fetch_at_addr();
 
         let (addr, op) = Decoder::decode(opcode);
 
         self.re_init(addr, op);
+        self.started = Some((OperationStart::Opcode(opcode), inter.reg.pc));
 
         inter.reg.pc += 1;
     }
 
+    /// Starts the interrupt sequence of `op` (IRQ or NMI) instead of fetching the next instruction.
+    pub fn interrupt(&mut self, inter: &mut CPUInterface, (op, start): (AnnotatedOpcode, OperationStart)) {
+        // the opcode is fetched, but ignored and the pc is not incremented
+        inter.mem.set_addr(
+            inter.reg.pc
+        );
+
+        inter.mem.read_at_addr();
+
+        self.re_init(&addressing::SIN, op);
+        self.started = Some((start, inter.reg.pc));
+    }
+
+    /// Returns the interrupt to be serviced before the next instruction, if any.
+    fn pending_interrupt(inter: &mut CPUInterface) -> Option<(AnnotatedOpcode, OperationStart)> {
+        let signals = inter.mem.signals_mut();
+
+        if signals.take_nmi() {
+            Some((operations::NMI, OperationStart::Nmi))
+        } else if signals.is_asserted(SignalLine::Irq) && (inter.reg.p & FLAG_INTERRUPT) == 0 {
+            Some((operations::IRQ, OperationStart::Irq))
+        } else {
+            None
+        }
+    }
+
+    /// Returns (once) the operation started since the last call and the pc it started at.
+    pub fn take_started(&mut self) -> Option<(OperationStart, u16)> {
+        self.started.take()
+    }
+
     fn unchecked_execute(&mut self, inter: &mut CPUInterface) {
         let mut owned_inserted = inter.next_cycle.take(); // consume and replace with None
 
@@ -74,11 +122,15 @@ impl CPUOperationManager {
 
     pub fn force_is_done(&mut self) {
         self.action_i = std::usize::MAX;
+        self.started = None;
     }
 
     pub fn execute(&mut self, inter: &mut CPUInterface) {
         if self.is_done(inter) {
-            self.fetch(inter);
+            match Self::pending_interrupt(inter) {
+                Some(op) => self.interrupt(inter, op),
+                None => self.fetch(inter),
+            }
 
             *inter.next_cycle = None;
         } else {
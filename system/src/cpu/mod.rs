@@ -3,4 +3,5 @@ mod opcode;
 
 mod register;
 
-pub use cpu::{CPU, CPUInterface};
\ No newline at end of file
+pub use cpu::{CPU, CPUInterface, OperationStart};
+pub use register::{CpuRegister, CpuFlag};
\ No newline at end of file
@@ -100,3 +100,29 @@ pub const SRT: [AddressingFn; 5] = [//stack RTS
     srt_4,
     srt_5,
 ];
+
+pub const SBK: [AddressingFn; 6] = [ //stack BRK
+    sbk_1,
+    sbk_2,
+    sbk_3,
+    sbk_4,
+    sbk_5,
+    sbk_6,
+];
+
+pub const SIN: [AddressingFn; 6] = [ //stack IRQ/NMI
+    sin_1,
+    sin_2,
+    sin_3,
+    sin_4,
+    sin_5,
+    sin_6,
+];
+
+pub const SRI: [AddressingFn; 5] = [ //stack RTI
+    sri_1,
+    sri_2,
+    sri_3,
+    sri_4,
+    sri_5,
+];
@@ -5,6 +5,9 @@ use crate::cpu::CPUInterface;
 use super::super::shared::{
     stack_push,
     stack_pull,
+    FLAG_BREAK,
+    FLAG_UNUSED,
+    FLAGS_PUSHED_ONLY,
 };
 
 //pub fn x_1(inter: &mut CPUInterface, _op_fn: InstructionFn, _op_mod: AddressingModifier) {}
@@ -394,3 +397,69 @@ pub fn srt_4(inter: &mut CPUInterface, _op_fn: InstructionFn, _op_mod: Addressin
 pub fn srt_5(inter: &mut CPUInterface, op_fn: InstructionFn, _op_mod: AddressingModifier) {
     op_fn(inter);
 }
+
+// ####### SBK (Stack Break) #######
+// The op fn sets the address bus to the vector and the interrupt disable flag.
+
+pub fn sbk_1(inter: &mut CPUInterface, _op_fn: InstructionFn, _op_mod: AddressingModifier) {
+    read_at_pc_inc(inter); // signature byte, ignored
+}
+
+pub use asb_3 as sbk_2;
+
+pub use asb_4 as sbk_3;
+
+pub fn sbk_4(inter: &mut CPUInterface, _op_fn: InstructionFn, _op_mod: AddressingModifier) {
+    stack_push(inter, inter.reg.p | FLAG_BREAK | FLAG_UNUSED);
+}
+
+pub fn sbk_5(inter: &mut CPUInterface, op_fn: InstructionFn, _op_mod: AddressingModifier) {
+    op_fn(inter);
+
+    inter.reg.itr = inter.mem.read_at_addr();
+}
+
+pub fn sbk_6(inter: &mut CPUInterface, _op_fn: InstructionFn, _op_mod: AddressingModifier) {
+    inter.mem.set_addr(inter.mem.addr() + 1);
+
+    let pch = inter.mem.read_at_addr();
+
+    inter.reg.pc = ((pch as u16) << 8) | inter.reg.itr as u16;
+}
+
+// ####### SIN (Stack Interrupt, IRQ and NMI) #######
+// Same as SBK, but the pc is not incremented and the break flag is pushed cleared.
+pub fn sin_1(inter: &mut CPUInterface, _op_fn: InstructionFn, _op_mod: AddressingModifier) {
+    inter.mem.set_addr(inter.reg.pc);
+    inter.mem.read_at_addr();
+}
+
+pub use sbk_2 as sin_2;
+
+pub use sbk_3 as sin_3;
+
+pub fn sin_4(inter: &mut CPUInterface, _op_fn: InstructionFn, _op_mod: AddressingModifier) {
+    stack_push(inter, (inter.reg.p & !FLAG_BREAK) | FLAG_UNUSED);
+}
+
+pub use sbk_5 as sin_5;
+
+pub use sbk_6 as sin_6;
+
+// ####### SRI (Stack Return from Interrupt) #######
+pub use waste_cycle as sri_1;
+
+pub use waste_cycle as sri_2;
+
+pub fn sri_3(inter: &mut CPUInterface, _op_fn: InstructionFn, _op_mod: AddressingModifier) {
+    inter.reg.p = stack_pull(inter) & !FLAGS_PUSHED_ONLY;
+}
+
+pub use srt_3 as sri_4;
+
+pub fn sri_5(inter: &mut CPUInterface, op_fn: InstructionFn, _op_mod: AddressingModifier) {
+    let pch = stack_pull(inter);
+    inter.mem.set_data(pch);
+
+    op_fn(inter);
+}
@@ -143,3 +143,9 @@ pub const BRK: AnnotatedOpcode = (brk, AddressingModifier::None);
 pub const NOP: AnnotatedOpcode = (nop, AddressingModifier::None);
 
 pub const RTI: AnnotatedOpcode = (rti, AddressingModifier::None);
+
+pub const IRQ: AnnotatedOpcode = (irq, AddressingModifier::None);
+
+pub const NMI: AnnotatedOpcode = (nmi, AddressingModifier::None);
+
+pub const KIL: AnnotatedOpcode = (kil, AddressingModifier::None);
@@ -3,6 +3,9 @@ use crate::cpu::CPUInterface;
 use super::super::shared::{
     stack_push,
     stack_pull,
+    FLAG_BREAK,
+    FLAG_UNUSED,
+    FLAGS_PUSHED_ONLY,
 };
 
 // ############### Const ###############
@@ -30,10 +33,6 @@ extern {
     fn explained_err(template: &str, code1: &str);
 }
 
-fn on_unsupported_feature(name: &str) {
-    CappedLogger::explained_err("wasmSys.unsupported", name);
-}
-
 //This should only be used when a feature such as decimal mode is not yet implemented,
 //but might be in the future.
 fn on_unimplemented_feature(name: &str) {
@@ -192,7 +191,7 @@ pub fn pha(inter: &mut CPUInterface) {
 }
 
 pub fn php(inter: &mut CPUInterface) {
-    stack_push(inter, inter.reg.p);
+    stack_push(inter, inter.reg.p | FLAG_BREAK | FLAG_UNUSED);
 }
 
 pub fn pla(inter: &mut CPUInterface) {
@@ -204,7 +203,7 @@ pub fn pla(inter: &mut CPUInterface) {
 }
 
 pub fn plp(inter: &mut CPUInterface) {
-    inter.reg.p = stack_pull(inter);
+    inter.reg.p = stack_pull(inter) & !FLAGS_PUSHED_ONLY;
 }
 
 /* #######################  Logical  ####################### */
@@ -452,8 +451,8 @@ pub fn cld(_inter: &mut CPUInterface) {
     on_unimplemented_feature("Decimal Mode");
 }
 
-pub fn cli(_inter: &mut CPUInterface) {
-    on_unsupported_feature("Interrupts");
+pub fn cli(inter: &mut CPUInterface) {
+    clear_flag(inter, FlagPositionOffset::Interrupt);
 }
 
 pub fn clv(inter: &mut CPUInterface) {
@@ -468,21 +467,40 @@ pub fn sed(_inter: &mut CPUInterface) {
     on_unimplemented_feature("Decimal Mode");
 }
 
-pub fn sei(_inter: &mut CPUInterface) {
-    on_unsupported_feature("Interrupts");
+pub fn sei(inter: &mut CPUInterface) {
+    set_flag(inter, FlagPositionOffset::Interrupt);
 }
 
 /* #######################  System Functions  ####################### */
-pub fn brk(_inter: &mut CPUInterface) {
-    on_unsupported_feature("Interrupts");
+// brk, irq and nmi are called by the interrupt sequence right before reading the vector.
+pub fn brk(inter: &mut CPUInterface) {
+    irq(inter);
+}
+
+pub fn irq(inter: &mut CPUInterface) {
+    set_flag(inter, FlagPositionOffset::Interrupt);
+
+    inter.mem.set_addr(0xFFFE);
+}
+
+pub fn nmi(inter: &mut CPUInterface) {
+    set_flag(inter, FlagPositionOffset::Interrupt);
+
+    inter.mem.set_addr(0xFFFA);
 }
 
 pub fn nop(_inter: &mut CPUInterface) {
     //do_nothing();
 }
 
-pub fn rti(_inter: &mut CPUInterface) {
-    on_unsupported_feature("Interrupts");
+pub fn rti(inter: &mut CPUInterface) {
+    set_pc_from_itr_and_data(inter);
+}
+
+// The NMOS 6502 jams on these opcodes until it's reset, the pc is moved back to the opcode so
+// it's executed forever.
+pub fn kil(inter: &mut CPUInterface) {
+    inter.reg.pc = inter.reg.pc.wrapping_sub(1);
 }
 
 
@@ -1,5 +1,12 @@
 use super::super::CPUInterface;
 
+/// Bits of `P` that only exist in its copies pushed to the stack, by `PHP`, `BRK` and the
+/// interrupts. They are dropped when `P` is pulled back.
+pub const FLAG_BREAK: u8 = 1 << 4;
+pub const FLAG_UNUSED: u8 = 1 << 5;
+
+pub const FLAGS_PUSHED_ONLY: u8 = FLAG_BREAK | FLAG_UNUSED;
+
 pub fn stack_push(inter: &mut CPUInterface, value: u8) {
     inter.mem.set_addr(0x0100);
     inter.mem.set_addr_lo(inter.reg.s);
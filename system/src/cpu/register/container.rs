@@ -74,7 +74,7 @@ impl RegisterContainer {
         self.x = 0;
         self.y = 0;
 
-        self.p = 0;
+        self.p = 0b0000_0100; // interrupts disabled, until the program is ready for them
 
         self.pc = 0;
         self.s = 0xFF;
@@ -0,0 +1,40 @@
+use wasm_bindgen::prelude::*;
+
+/// Registers visible to the programmer, see `RegisterContainer`.
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Copy, Clone)]
+pub enum CpuRegister {
+    A = 0,
+    X = 1,
+    Y = 2,
+    P = 3,
+    S = 4,
+    Pc = 5,
+}
+
+impl CpuRegister {
+    /// Returns true if the register has 16 bits.
+    pub fn is_wide(&self) -> bool {
+        matches!(self, CpuRegister::Pc)
+    }
+}
+
+/// Flags of the P register, the values are their bit position.
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Copy, Clone)]
+pub enum CpuFlag {
+    Carry = 0,
+    Zero = 1,
+    Interrupt = 2,
+    Decimal = 3,
+    Overflow = 6,
+    Negative = 7,
+}
+
+impl CpuFlag {
+    pub fn mask(&self) -> u8 {
+        1 << *self as u8
+    }
+}
@@ -1,5 +1,6 @@
 mod generic;
 mod container;
+mod id;
 
 pub use generic::GenericRegister;
 
@@ -7,4 +8,5 @@ pub type DataRegister = GenericRegister<u8>;
 pub type AddrRegister = GenericRegister<u16>;
 
 pub use container::RegisterContainer;
+pub use id::{CpuRegister, CpuFlag};
 
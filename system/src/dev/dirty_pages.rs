@@ -0,0 +1,48 @@
+/// Size of the pages tracked by `DirtyPages`, the same as a 6502 page.
+pub const PAGE_SIZE: u16 = 0x100;
+
+/// Keeps track of the pages of a device that changed since they were last taken, so views of
+/// the memory only need to copy those.
+pub struct DirtyPages {
+    pages: Vec<bool>,
+}
+
+impl DirtyPages {
+    /// Creates the tracker for a device of `size` bytes, with every page dirty.
+    pub fn with_size(size: u16) -> Self {
+        DirtyPages {
+            pages: vec![true; page_count(size)],
+        }
+    }
+
+    #[inline(always)]
+    pub fn mark(&mut self, offset: u16) {
+        self.pages[(offset / PAGE_SIZE) as usize] = true;
+    }
+
+    pub fn mark_all(&mut self) {
+        for page in &mut self.pages {
+            *page = true;
+        }
+    }
+
+    /// Returns the dirty pages (in increasing order) and marks them as clean.
+    pub fn take(&mut self) -> Vec<u16> {
+        let dirty = self.pages.iter()
+            .enumerate()
+            .filter(|(_, dirty)| **dirty)
+            .map(|(page, _)| page as u16)
+            .collect();
+
+        for page in &mut self.pages {
+            *page = false;
+        }
+
+        dirty
+    }
+}
+
+/// Number of pages needed for a device of `size` bytes.
+pub fn page_count(size: u16) -> usize {
+    (size as usize).div_ceil(PAGE_SIZE as usize)
+}
@@ -1,5 +1,6 @@
 use super::{BoxedDev, DeviceId};
 use crate::dev::{mem, io};
+use crate::system::SystemError;
 
 // We cant have constructors in traits.
 pub struct DeviceFactory {}
@@ -11,18 +12,20 @@ impl DeviceFactory {
     /// If the device has a fixed size, `size` must be a 0, and the final size can be checked with `self.size()`.
     ///
     /// # Returns
-    ///  Returns `Ok(...)` if the device size is valid and `Err(())` if not.
-    ///
-    /// ## Err type
-    /// In the future, `E` might contain some kind of error, but for now, the only error is an invalid size.
-    pub fn with_size(dev_type: DeviceId, size: u16) -> Result<BoxedDev, ()> {
+    ///  Returns `Ok(...)` if the device size is valid, `Err(SystemError::InvalidSize)` if not,
+    ///  and `Err(SystemError::UnknownDeviceType)` for the devices that can't be created here.
+    pub fn with_size(dev_type: DeviceId, size: u16) -> Result<BoxedDev, SystemError> {
         let has_fixed_sz = dev_type.fixed_size().is_some();
 
         if (size == 0 && !has_fixed_sz) || (size != 0 && has_fixed_sz) {
-            Err(())
+            Err(SystemError::InvalidSize {
+                type_name: dev_type.name().to_string(),
+                size,
+            })
         } else {
             match dev_type {
-                DeviceId::CPU => Err(()),
+                // custom devices are created by `DeviceRegistry`
+                DeviceId::CPU | DeviceId::Custom => Err(SystemError::UnknownDeviceType(dev_type.name().to_string())),
 
                 DeviceId::PixelScreen => {
                     Ok(Box::new(
@@ -30,12 +33,84 @@ impl DeviceFactory {
                     ))
                 }
 
+                DeviceId::CharLcd => {
+                    Ok(Box::new(
+                        io::CharLcd::new()
+                    ))
+                }
+
+                DeviceId::ColorScreen => {
+                    Ok(Box::new(
+                        io::PixelScreen::color_screen()
+                    ))
+                }
+
                 DeviceId::AsciiIOBuffer => {
                     Ok(Box::new(
                         io::AsciiIOBuffer::new()
                     ))
                 }
 
+                DeviceId::Acia => {
+                    Ok(Box::new(
+                        io::Acia::new()
+                    ))
+                }
+
+                DeviceId::KeyboardMatrix => {
+                    Ok(Box::new(
+                        io::KeyboardMatrix::new()
+                    ))
+                }
+
+                DeviceId::Joystick => {
+                    Ok(Box::new(
+                        io::Joystick::new()
+                    ))
+                }
+
+                DeviceId::KeyLatch => {
+                    Ok(Box::new(
+                        io::KeyLatch::new()
+                    ))
+                }
+
+                DeviceId::Semihost => {
+                    Ok(Box::new(
+                        io::Semihost::new()
+                    ))
+                }
+
+                DeviceId::SoundGenerator => {
+                    Ok(Box::new(
+                        io::SoundGenerator::new()
+                    ))
+                }
+
+                DeviceId::Prng => {
+                    Ok(Box::new(
+                        io::Prng::new()
+                    ))
+                }
+
+                DeviceId::RealTimeClock => {
+                    Ok(Box::new(
+                        io::RealTimeClock::new()
+                    ))
+                }
+
+                DeviceId::Via => {
+                    Ok(Box::new(
+                        io::Via::new()
+                    ))
+                }
+
+                DeviceId::Pia => {
+                    Ok(Box::new(
+                        io::Pia::new()
+                    ))
+                }
+
                 DeviceId::Rom => {
                     Ok(Box::new(
                         mem::Rom::with_size(size)
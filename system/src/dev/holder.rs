@@ -2,7 +2,8 @@ use crate::dev::BoxedDev;
 use std::ops::Range;
 
 pub struct DeviceHolder {
-    range: Range<u16>,
+    // the end is exclusive, so it doesn't fit in a u16 for devices mapped up to 0xFFFF
+    range: Range<u32>,
 
     device: BoxedDev,
 
@@ -10,10 +11,10 @@ pub struct DeviceHolder {
 }
 
 impl DeviceHolder {
-    pub fn new(device: BoxedDev, start: u16, end: u16, uid: u16) -> Self {
+    pub fn new(device: BoxedDev, start: u16, end: u32, uid: u16) -> Self {
         DeviceHolder {
             range: Range {
-                start,
+                start: start as u32,
                 end,
             },
 
@@ -30,10 +31,23 @@ impl DeviceHolder {
         &self.device
     }
 
-    pub fn range(&self) -> &Range<u16> {
+    pub fn range(&self) -> &Range<u32> {
         &self.range
     }
 
+    /// Maps the device to another range, the caller must make sure it's valid.
+    pub fn set_range(&mut self, start: u16, end: u32) {
+        self.range = Range {
+            start: start as u32,
+            end,
+        };
+    }
+
+    /// Replaces the device, keeping the range and the uid. Returns the old device.
+    pub fn replace_device(&mut self, device: BoxedDev) -> BoxedDev {
+        std::mem::replace(&mut self.device, device)
+    }
+
     pub fn uid(&self) -> u16 {
         self.uid
     }
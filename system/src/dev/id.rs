@@ -6,13 +6,27 @@ use wasm_bindgen::prelude::*;
 pub enum DeviceId {
     // IO
     PixelScreen = 10,
+    CharLcd = 11,
+    ColorScreen = 12,
     AsciiIOBuffer = 20,
+    Acia = 21,
+    KeyboardMatrix = 22,
+    Joystick = 23,
+    KeyLatch = 24,
+    Semihost = 25,
+    SoundGenerator = 30,
+    Prng = 40,
+    RealTimeClock = 41,
+    Via = 50,
+    Pia = 51,
 
     // MEM
     Rom = 100,
     Ram = 101,
 
     // Special
+    /// Devices created from a type registered at runtime, see `DeviceRegistry`.
+    Custom = 254,
     CPU = 255,
 }
 
@@ -21,12 +35,57 @@ impl DeviceId {
         match self {
             // keep in sync with PixelScreen width and height
             Self::PixelScreen => Some(50 * 50),
+            Self::CharLcd => Some(2),
+            // keep in sync with the PixelScreen::color_screen width and height
+            Self::ColorScreen => Some(32 * 32),
             Self::AsciiIOBuffer => Some(1),
+            Self::Acia => Some(4),
+            Self::KeyboardMatrix => Some(2),
+            Self::Joystick => Some(1),
+            Self::KeyLatch => Some(1),
+            // keep in sync with the Semihost register layout
+            Self::Semihost => Some(8),
+            // keep in sync with the SoundGenerator register layout
+            Self::SoundGenerator => Some(4 * 4),
+            Self::Prng => Some(1),
+            // keep in sync with the RealTimeClock register layout
+            Self::RealTimeClock => Some(12),
+            Self::Via => Some(16),
+            Self::Pia => Some(4),
 
             Self::Rom => None,
             Self::Ram => None,
 
+            // the size is defined by the registered type
+            Self::Custom => None,
+
             Self::CPU => Some(0),
         }
     }
+
+    /// Name of the device type, as registered by `DeviceRegistry::new`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::PixelScreen => "PixelScreen",
+            Self::CharLcd => "CharLcd",
+            Self::ColorScreen => "ColorScreen",
+            Self::AsciiIOBuffer => "AsciiIOBuffer",
+            Self::Acia => "Acia",
+            Self::KeyboardMatrix => "KeyboardMatrix",
+            Self::Joystick => "Joystick",
+            Self::KeyLatch => "KeyLatch",
+            Self::Semihost => "Semihost",
+            Self::SoundGenerator => "SoundGenerator",
+            Self::Prng => "Prng",
+            Self::RealTimeClock => "RealTimeClock",
+            Self::Via => "Via",
+            Self::Pia => "Pia",
+
+            Self::Rom => "Rom",
+            Self::Ram => "Ram",
+
+            Self::Custom => "Custom",
+            Self::CPU => "CPU",
+        }
+    }
 }
@@ -0,0 +1,273 @@
+use std::any::Any;
+use std::cell::Cell;
+
+use js_sys::Map;
+
+use super::super::{DeviceTrait, AddressableDeviceTrait, DeviceId, SignalLines, SignalLine, utils, CLOCK_HZ};
+use super::{SerialBackend, BufferSerial};
+
+// 6551 Asynchronous Communications Interface Adapter.
+//
+// Register layout:
+//
+//  +0 data     read: receiver data register, write: transmitter data register
+//  +1 status   read: see STATUS_*, write: programmed reset
+//  +2 command  see CMD_*
+//  +3 control  baud rate (bits 0-3), word length (bits 5-6) and stop bits (bit 7)
+//
+// The transmitted bytes take as long as the selected baud rate says (using `CLOCK_HZ`), and new bytes
+// are only received once the previous one has been read, so the host can't overrun the program.
+
+const REG_DATA: u16 = 0;
+const REG_STATUS: u16 = 1;
+const REG_COMMAND: u16 = 2;
+const REG_CONTROL: u16 = 3;
+
+const STATUS_OVERRUN: u8 = 1 << 2;
+const STATUS_RDRF: u8 = 1 << 3; // receiver data register full
+const STATUS_TDRE: u8 = 1 << 4; // transmitter data register empty
+const STATUS_IRQ: u8 = 1 << 7;
+
+const CMD_DTR: u8 = 1 << 0; // enables the receiver and interrupts
+const CMD_RX_IRQ_DISABLED: u8 = 1 << 1;
+const CMD_TX_CONTROL_MASK: u8 = 0b11 << 2;
+const CMD_TX_IRQ_ENABLED: u8 = 0b01 << 2;
+const CMD_ECHO: u8 = 1 << 4;
+const CMD_PARITY_ENABLED: u8 = 1 << 5;
+
+// Bits 5-7 survive a programmed reset
+const CMD_PROGRAMMED_RESET_MASK: u8 = 0b1110_0000;
+
+const CTRL_BAUD_MASK: u8 = 0x0F;
+const CTRL_TWO_STOP_BITS: u8 = 1 << 7;
+
+/// Indexed by the control register baud bits, `0` is "16x external clock", which is not emulated,
+/// so the fastest rate common in host terminals is used instead.
+const BAUD_RATES: [u32; 16] = [
+    115_200, 50, 75, 110, 135, 150, 300, 600,
+    1200, 1800, 2400, 3600, 4800, 7200, 9600, 19_200,
+];
+
+pub struct Acia {
+    backend: Box<dyn SerialBackend>,
+
+    rx_data: u8,
+    tx_data: u8,
+    command: u8,
+    control: u8,
+
+    // reads only get a shared reference, and reading the status/data registers clears some flags.
+    status: Cell<u8>,
+
+    /// Byte being sent and the remaining cycles until it's done.
+    tx_shift: Option<(u8, u32)>,
+
+    /// Cycles until the host input is checked again.
+    rx_wait: u32,
+}
+
+impl Acia {
+    pub fn new() -> Self {
+        Acia {
+            backend: Box::new(BufferSerial::new()),
+
+            rx_data: 0,
+            tx_data: 0,
+            command: 0,
+            control: 0,
+
+            status: Cell::new(STATUS_TDRE),
+
+            tx_shift: None,
+            rx_wait: 0,
+        }
+    }
+
+    /// Replaces the host side of the device, by default a `BufferSerial` is used.
+    pub fn connect(&mut self, backend: Box<dyn SerialBackend>) {
+        self.backend = backend;
+    }
+
+    /// Returns the current backend as a `T`, if it is one.
+    pub fn backend_as_mut<T: SerialBackend>(&mut self) -> Option<&mut T> {
+        let backend: &mut dyn Any = self.backend.as_mut();
+
+        backend.downcast_mut::<T>()
+    }
+
+    fn set_status(&self, flags: u8, value: bool) {
+        let status = self.status.get();
+
+        self.status.set(if value { status | flags } else { status & !flags });
+    }
+
+    fn word_mask(&self) -> u8 {
+        let word_len = 8 - ((self.control >> 5) & 0b11);
+
+        (0xFF_u16 >> (8 - word_len)) as u8
+    }
+
+    /// Cycles needed to send or receive a full character (start bit, data, parity and stop bits).
+    fn char_cycles(&self) -> u32 {
+        let baud = BAUD_RATES[(self.control & CTRL_BAUD_MASK) as usize];
+
+        let data_bits = self.word_mask().count_ones();
+        let parity_bits = u32::from((self.command & CMD_PARITY_ENABLED) != 0);
+        let stop_bits = if (self.control & CTRL_TWO_STOP_BITS) != 0 { 2 } else { 1 };
+
+        let bits = 1 + data_bits + parity_bits + stop_bits;
+
+        (CLOCK_HZ / baud * bits).max(1)
+    }
+
+    fn receiver_enabled(&self) -> bool {
+        (self.command & CMD_DTR) != 0
+    }
+
+    fn transmitter_enabled(&self) -> bool {
+        (self.command & CMD_TX_CONTROL_MASK) != 0
+    }
+
+    fn tick_transmitter(&mut self) {
+        if let Some((value, cycles)) = self.tx_shift {
+            if cycles > 1 {
+                self.tx_shift = Some((value, cycles - 1));
+            } else {
+                self.tx_shift = None;
+                self.backend.write_byte(value);
+            }
+        }
+
+        let has_pending = (self.status.get() & STATUS_TDRE) == 0;
+
+        if self.tx_shift.is_none() && has_pending && self.transmitter_enabled() {
+            self.tx_shift = Some((self.tx_data, self.char_cycles()));
+            self.set_status(STATUS_TDRE, true);
+
+            if self.receiver_enabled() && (self.command & CMD_TX_CONTROL_MASK) == CMD_TX_IRQ_ENABLED {
+                self.set_status(STATUS_IRQ, true);
+            }
+        }
+    }
+
+    fn tick_receiver(&mut self) {
+        if self.rx_wait > 0 {
+            self.rx_wait -= 1;
+            return;
+        }
+
+        self.rx_wait = self.char_cycles();
+
+        let is_full = (self.status.get() & STATUS_RDRF) != 0;
+
+        if !self.receiver_enabled() || is_full {
+            return;
+        }
+
+        if let Some(value) = self.backend.read_byte() {
+            self.rx_data = value & self.word_mask();
+            self.set_status(STATUS_RDRF, true);
+
+            if (self.command & CMD_ECHO) != 0 {
+                self.backend.write_byte(self.rx_data);
+            }
+
+            if (self.command & CMD_RX_IRQ_DISABLED) == 0 {
+                self.set_status(STATUS_IRQ, true);
+            }
+        }
+    }
+}
+
+impl DeviceTrait for Acia {
+    fn tick(&mut self) {
+        self.tick_transmitter();
+        self.tick_receiver();
+    }
+
+    fn reset_system(&mut self) {
+        self.rx_data = 0;
+        self.tx_data = 0;
+        self.command = 0;
+        self.control = 0;
+
+        self.status.set(STATUS_TDRE);
+
+        self.tx_shift = None;
+        self.rx_wait = 0;
+    }
+
+    fn reset_hard(&mut self) {
+        self.reset_system();
+    }
+
+    fn update_widget(&mut self, pkg: &Map) {
+        utils::js_map_add_entry_f64(pkg, "status", self.status.get());
+        utils::js_map_add_entry_f64(pkg, "command", self.command);
+        utils::js_map_add_entry_f64(pkg, "control", self.control);
+    }
+
+    fn drive_signals(&self, signals: &mut SignalLines) {
+        if (self.status.get() & STATUS_IRQ) != 0 {
+            signals.assert(SignalLine::Irq);
+        }
+    }
+
+    fn device_id(&self) -> DeviceId {
+        DeviceId::Acia
+    }
+}
+
+impl AddressableDeviceTrait for Acia {
+    fn size(&self) -> u16 {
+        DeviceId::fixed_size(&DeviceId::Acia).unwrap()
+    }
+
+    fn read_unchecked(&self, offset: u16) -> u8 {
+        match offset {
+            REG_DATA => {
+                self.set_status(STATUS_RDRF | STATUS_OVERRUN, false);
+
+                self.rx_data
+            }
+
+            REG_STATUS => {
+                let status = self.status.get();
+                self.set_status(STATUS_IRQ, false);
+
+                status
+            }
+
+            REG_COMMAND => self.command,
+            _ => self.control,
+        }
+    }
+
+    fn peek_unchecked(&self, offset: u16) -> u8 {
+        match offset {
+            REG_DATA => self.rx_data,
+            REG_STATUS => self.status.get(),
+
+            _ => self.read_unchecked(offset),
+        }
+    }
+
+    fn write_unchecked(&mut self, offset: u16, value: u8) {
+        match offset {
+            REG_DATA => {
+                self.tx_data = value & self.word_mask();
+                self.set_status(STATUS_TDRE, false);
+            }
+
+            REG_STATUS => {
+                self.command &= CMD_PROGRAMMED_RESET_MASK;
+                self.set_status(STATUS_OVERRUN, false);
+            }
+
+            REG_COMMAND => self.command = value,
+            REG_CONTROL => self.control = value,
+
+            _ => {}
+        }
+    }
+}
@@ -56,6 +56,13 @@ impl AddressableDeviceTrait for AsciiIOBuffer {
            .map_or(0, |val| val as u8)
     }
 
+    fn peek_unchecked(&self, _offset: u16) -> u8 {
+        // the next read pops the last element
+        self.ascii_in.get(self.ascii_in.length().wrapping_sub(1))
+            .as_f64()
+            .map_or(0, |val| val as u8)
+    }
+
     fn write_unchecked(&mut self, _offset: u16, value: u8) {
         let char_arr = [value];
         let char_str = &std::str::from_utf8(&char_arr);
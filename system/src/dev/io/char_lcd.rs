@@ -0,0 +1,540 @@
+use std::cell::Cell;
+
+use js_sys::{Map, Array, Uint8Array};
+use wasm_bindgen::JsValue;
+
+use super::super::{DeviceTrait, AddressableDeviceTrait, DeviceId, utils};
+
+// HD44780 character LCD controller.
+//
+// It can be connected directly to the bus:
+//
+//  +0 read: busy flag (bit 7) and address counter, write: instruction
+//  +1 read/write: data (DDRAM or CGRAM, depending on the last address set)
+//
+// Or driven through IO ports (`DeviceTrait::set_port_input`), like most breadboard builds do with a VIA:
+//
+//  PORT_DATA     D0-D7 (only D4-D7 in 4 bit mode)
+//  PORT_CONTROL  E (bit 7), RW (bit 6), RS (bit 5). Writes are latched on the E falling edge,
+//                and the data pins are driven by the device while E is high on reads.
+//
+// Instructions are executed even if the device is busy, the busy flag is only informative.
+
+pub const PORT_DATA: u8 = 0;
+pub const PORT_CONTROL: u8 = 1;
+
+pub const CONTROL_E: u8 = 1 << 7;
+pub const CONTROL_RW: u8 = 1 << 6;
+pub const CONTROL_RS: u8 = 1 << 5;
+
+const REG_INSTRUCTION: u16 = 0;
+
+const DEFAULT_COLUMNS: u8 = 16;
+const DEFAULT_ROWS: u8 = 2;
+
+const DDRAM_SIZE: usize = 0x80;
+const CGRAM_SIZE: usize = 0x40;
+
+/// Start of each row in DDRAM (in 2 line mode). Rows 2 and 3 continue rows 0 and 1, as in 20x4 modules.
+const ROW_OFFSETS: [u8; 4] = [0x00, 0x40, 0x14, 0x54];
+const LINE_LEN_2_LINES: u8 = 40;
+const LINE_LEN_1_LINE: u8 = 80;
+
+// Instructions, identified by their highest set bit
+const INS_CLEAR: u8 = 0x01;
+const INS_HOME: u8 = 0x02;
+const INS_ENTRY_MODE: u8 = 0x04;
+const INS_DISPLAY_CONTROL: u8 = 0x08;
+const INS_SHIFT: u8 = 0x10;
+const INS_FUNCTION_SET: u8 = 0x20;
+const INS_SET_CGRAM: u8 = 0x40;
+const INS_SET_DDRAM: u8 = 0x80;
+
+const ENTRY_INCREMENT: u8 = 1 << 1;
+const ENTRY_SHIFT: u8 = 1 << 0;
+
+const DISPLAY_ON: u8 = 1 << 2;
+const DISPLAY_CURSOR: u8 = 1 << 1;
+const DISPLAY_BLINK: u8 = 1 << 0;
+
+const SHIFT_DISPLAY: u8 = 1 << 3;
+const SHIFT_RIGHT: u8 = 1 << 2;
+
+const FUNCTION_8_BIT: u8 = 1 << 4;
+const FUNCTION_2_LINES: u8 = 1 << 3;
+
+// Execution times in cycles (µs at 1MHz)
+const SLOW_INSTRUCTION_CYCLES: u32 = 1520;
+const INSTRUCTION_CYCLES: u32 = 37;
+const DATA_CYCLES: u32 = 41;
+
+pub struct CharLcd {
+    columns: u8,
+    rows: u8,
+
+    ddram: [u8; DDRAM_SIZE],
+    cgram: [u8; CGRAM_SIZE],
+
+    // reads only get a shared reference, but reading data moves the address counter.
+    address: Cell<u8>,
+    cgram_selected: bool,
+
+    entry_mode: u8,
+    display_control: u8,
+    function: u8,
+
+    /// Amount of positions the display has been shifted to the left.
+    display_shift: u8,
+
+    busy_cycles: u32,
+
+    /// In 4 bit mode, the high nibble received while waiting for the low one.
+    pending_nibble: Option<u8>,
+
+    data_pins: u8,
+    control_pins: u8,
+    /// Value driven into the data pins during a read.
+    read_latch: Option<u8>,
+
+    widget_update: bool,
+}
+
+impl CharLcd {
+    pub fn new() -> Self {
+        Self::with_dimensions(DEFAULT_COLUMNS, DEFAULT_ROWS)
+    }
+
+    /// Creates a display with `columns` (up to 40) and `rows` (up to 4) visible characters.
+    pub fn with_dimensions(columns: u8, rows: u8) -> Self {
+        let mut tmp = CharLcd {
+            columns: columns.clamp(1, LINE_LEN_2_LINES),
+            rows: rows.clamp(1, ROW_OFFSETS.len() as u8),
+
+            ddram: [b' '; DDRAM_SIZE],
+            cgram: [0; CGRAM_SIZE],
+
+            address: Cell::new(0),
+            cgram_selected: false,
+
+            entry_mode: ENTRY_INCREMENT,
+            display_control: 0,
+            function: FUNCTION_8_BIT,
+
+            display_shift: 0,
+
+            busy_cycles: 0,
+
+            pending_nibble: None,
+
+            data_pins: 0,
+            control_pins: 0,
+            read_latch: None,
+
+            widget_update: true,
+        };
+
+        tmp.reset_hard();
+
+        tmp
+    }
+
+    fn two_lines(&self) -> bool {
+        (self.function & FUNCTION_2_LINES) != 0
+    }
+
+    fn line_len(&self) -> u8 {
+        if self.two_lines() { LINE_LEN_2_LINES } else { LINE_LEN_1_LINE }
+    }
+
+    /// Returns the character codes currently visible, row by row.
+    pub fn visible_codes(&self) -> Vec<Vec<u8>> {
+        let line_len = self.line_len();
+        let blank = vec![b' '; self.columns as usize];
+
+        (0..self.rows)
+            .map(|row| {
+                if (self.display_control & DISPLAY_ON) == 0 {
+                    return blank.clone();
+                }
+
+                // in 1 line mode, the second row (if any) is just not driven
+                if !self.two_lines() && row > 0 {
+                    return blank.clone();
+                }
+
+                let (line, line_offset) = if self.two_lines() {
+                    (ROW_OFFSETS[row as usize] & 0x40, ROW_OFFSETS[row as usize] & 0x3F)
+                } else {
+                    (0, 0)
+                };
+
+                (0..self.columns)
+                    .map(|column| {
+                        let pos = (line_offset + column + self.display_shift) % line_len;
+
+                        self.ddram[(line + pos) as usize]
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the visible text, one line per row. Custom (CGRAM) characters are shown as `?`.
+    pub fn text(&self) -> String {
+        self.visible_codes()
+            .iter()
+            .map(|row| row.iter().map(|code| char_for_code(*code)).collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// Position of the cursor in the visible area (row, column), if it's enabled and visible.
+    fn visible_cursor(&self) -> Option<(u8, u8)> {
+        let cursor_on = (self.display_control & (DISPLAY_CURSOR | DISPLAY_BLINK)) != 0;
+
+        if (self.display_control & DISPLAY_ON) == 0 || !cursor_on || self.cgram_selected {
+            return None;
+        }
+
+        let address = self.address.get();
+        let line_len = self.line_len();
+
+        (0..self.rows).find_map(|row| {
+            if !self.two_lines() && row > 0 {
+                return None;
+            }
+
+            let row_start = if self.two_lines() { ROW_OFFSETS[row as usize] } else { 0 };
+
+            let line = row_start & 0x40;
+            if (address & 0x40) != line {
+                return None;
+            }
+
+            let pos = address & 0x3F;
+            let pos = if self.two_lines() { pos } else { address };
+
+            let first = ((row_start & 0x3F) + self.display_shift) % line_len;
+            let column = (pos + line_len - first) % line_len;
+
+            if column < self.columns { Some((row, column)) } else { None }
+        })
+    }
+
+    fn status(&self) -> u8 {
+        let busy = if self.busy_cycles > 0 { 0x80 } else { 0 };
+
+        busy | (self.address.get() & 0x7F)
+    }
+
+    fn step_address(&self) {
+        let address = self.address.get();
+        let increment = (self.entry_mode & ENTRY_INCREMENT) != 0;
+
+        let next = if self.cgram_selected {
+            let next = if increment { address + 1 } else { address.wrapping_sub(1) };
+
+            next % CGRAM_SIZE as u8
+        } else if self.two_lines() {
+            let line = address & 0x40;
+            let pos = address & 0x3F;
+
+            match (increment, pos) {
+                (true, p) if p + 1 >= LINE_LEN_2_LINES => line ^ 0x40,
+                (true, p) => line | (p + 1),
+                (false, 0) => (line ^ 0x40) | (LINE_LEN_2_LINES - 1),
+                (false, p) => line | (p - 1),
+            }
+        } else if increment {
+            (address + 1) % LINE_LEN_1_LINE
+        } else if address == 0 {
+            LINE_LEN_1_LINE - 1
+        } else {
+            address - 1
+        };
+
+        self.address.set(next);
+    }
+
+    fn shift_display(&mut self, right: bool) {
+        let line_len = self.line_len();
+
+        self.display_shift = if right {
+            (self.display_shift + line_len - 1) % line_len
+        } else {
+            (self.display_shift + 1) % line_len
+        };
+    }
+
+    fn execute_instruction(&mut self, value: u8) {
+        self.busy_cycles = INSTRUCTION_CYCLES;
+        self.widget_update = true;
+
+        if value & INS_SET_DDRAM != 0 {
+            self.cgram_selected = false;
+            self.address.set(value & 0x7F);
+        } else if value & INS_SET_CGRAM != 0 {
+            self.cgram_selected = true;
+            self.address.set(value & 0x3F);
+        } else if value & INS_FUNCTION_SET != 0 {
+            self.function = value;
+            self.pending_nibble = None;
+        } else if value & INS_SHIFT != 0 {
+            let right = (value & SHIFT_RIGHT) != 0;
+
+            if (value & SHIFT_DISPLAY) != 0 {
+                self.shift_display(right);
+            } else {
+                let old_mode = self.entry_mode;
+
+                self.entry_mode = if right { ENTRY_INCREMENT } else { 0 };
+                self.step_address();
+                self.entry_mode = old_mode;
+            }
+        } else if value & INS_DISPLAY_CONTROL != 0 {
+            self.display_control = value;
+        } else if value & INS_ENTRY_MODE != 0 {
+            self.entry_mode = value;
+        } else if value & INS_HOME != 0 {
+            self.busy_cycles = SLOW_INSTRUCTION_CYCLES;
+
+            self.cgram_selected = false;
+            self.address.set(0);
+            self.display_shift = 0;
+        } else if value & INS_CLEAR != 0 {
+            self.busy_cycles = SLOW_INSTRUCTION_CYCLES;
+
+            self.ddram = [b' '; DDRAM_SIZE];
+            self.cgram_selected = false;
+            self.address.set(0);
+            self.display_shift = 0;
+            self.entry_mode |= ENTRY_INCREMENT;
+        }
+    }
+
+    fn write_data(&mut self, value: u8) {
+        self.busy_cycles = DATA_CYCLES;
+        self.widget_update = true;
+
+        let address = self.address.get() as usize;
+
+        if self.cgram_selected {
+            self.cgram[address % CGRAM_SIZE] = value;
+        } else {
+            self.ddram[address % DDRAM_SIZE] = value;
+
+            if (self.entry_mode & ENTRY_SHIFT) != 0 {
+                self.shift_display((self.entry_mode & ENTRY_INCREMENT) == 0);
+            }
+        }
+
+        self.step_address();
+    }
+
+    fn read_data(&self) -> u8 {
+        let value = self.peek_data();
+
+        self.step_address();
+
+        value
+    }
+
+    fn peek_data(&self) -> u8 {
+        let address = self.address.get() as usize;
+
+        if self.cgram_selected {
+            self.cgram[address % CGRAM_SIZE]
+        } else {
+            self.ddram[address % DDRAM_SIZE]
+        }
+    }
+
+    /// Handles a write coming from the bus or the ports, assembling both nibbles in 4 bit mode.
+    fn write(&mut self, is_data: bool, value: u8) {
+        let value = if (self.function & FUNCTION_8_BIT) != 0 {
+            value
+        } else {
+            match self.pending_nibble.take() {
+                Some(high) => high | (value >> 4),
+
+                None => {
+                    self.pending_nibble = Some(value & 0xF0);
+                    return;
+                }
+            }
+        };
+
+        if is_data {
+            self.write_data(value);
+        } else {
+            self.execute_instruction(value);
+        }
+    }
+
+    fn set_control_pins(&mut self, value: u8) {
+        let old = self.control_pins;
+        self.control_pins = value;
+
+        let rose = (old & CONTROL_E) == 0 && (value & CONTROL_E) != 0;
+        let fell = (old & CONTROL_E) != 0 && (value & CONTROL_E) == 0;
+
+        let is_data = (value & CONTROL_RS) != 0;
+        let is_read = (value & CONTROL_RW) != 0;
+
+        if rose && is_read {
+            let full = if is_data { self.read_data() } else { self.status() };
+
+            let driven = if (self.function & FUNCTION_8_BIT) != 0 {
+                full
+            } else {
+                // in 4 bit mode, reads alternate the high and the low nibble
+                match self.pending_nibble.take() {
+                    Some(low) => low,
+
+                    None => {
+                        self.pending_nibble = Some(full << 4);
+
+                        full & 0xF0
+                    }
+                }
+            };
+
+            self.read_latch = Some(driven);
+        } else if fell {
+            if is_read {
+                self.read_latch = None;
+            } else {
+                self.write(is_data, self.data_pins);
+            }
+        }
+    }
+}
+
+/// Converts a character code (A00 character ROM) into the closest char.
+fn char_for_code(code: u8) -> char {
+    match code {
+        0x5C => '¥',
+        0x7E => '→',
+        0x7F => '←',
+        0x20..=0x7D => code as char,
+
+        _ => '?',
+    }
+}
+
+impl DeviceTrait for CharLcd {
+    fn tick(&mut self) {
+        if self.busy_cycles > 0 {
+            self.busy_cycles -= 1;
+        }
+    }
+
+    fn reset_system(&mut self) {
+        // contents are kept as a real display would (unless power is removed)
+        self.address.set(0);
+        self.cgram_selected = false;
+
+        self.entry_mode = ENTRY_INCREMENT;
+        self.display_control = 0;
+        self.function = FUNCTION_8_BIT;
+        self.display_shift = 0;
+
+        self.busy_cycles = 0;
+        self.pending_nibble = None;
+
+        self.data_pins = 0;
+        self.control_pins = 0;
+        self.read_latch = None;
+
+        self.widget_update = true;
+    }
+
+    fn reset_hard(&mut self) {
+        self.ddram = [b' '; DDRAM_SIZE];
+        self.cgram = [0; CGRAM_SIZE];
+
+        self.reset_system();
+    }
+
+    fn setup_widget(&mut self, pkg: &Map) {
+        utils::js_map_add_entry_f64(pkg, "columns", self.columns);
+        utils::js_map_add_entry_f64(pkg, "rows", self.rows);
+
+        self.widget_update = true;
+        self.update_widget(pkg);
+    }
+
+    fn update_widget(&mut self, pkg: &Map) {
+        utils::js_map_add_entry_bool(pkg, "update", self.widget_update);
+
+        if !self.widget_update {
+            return;
+        }
+
+        self.widget_update = false;
+
+        let codes = self.visible_codes().concat();
+        pkg.set(&JsValue::from_str("codes"), &Uint8Array::from(codes.as_slice()));
+        pkg.set(&JsValue::from_str("cgram"), &Uint8Array::from(&self.cgram[..]));
+
+        let text = Array::new();
+        for line in self.text().lines() {
+            text.push(&JsValue::from_str(line));
+        }
+        pkg.set(&JsValue::from_str("text"), &text);
+
+        let cursor = self.visible_cursor()
+            .map_or(-1.0, |(row, column)| (row as u32 * self.columns as u32 + column as u32) as f64);
+        utils::js_map_add_entry_f64(pkg, "cursor", cursor);
+
+        utils::js_map_add_entry_bool(pkg, "blink", (self.display_control & DISPLAY_BLINK) != 0);
+        utils::js_map_add_entry_bool(pkg, "displayOn", (self.display_control & DISPLAY_ON) != 0);
+    }
+
+    fn port_output(&self, port: u8) -> Option<u8> {
+        match port {
+            PORT_DATA => self.read_latch,
+
+            _ => None,
+        }
+    }
+
+    fn set_port_input(&mut self, port: u8, value: u8) {
+        match port {
+            PORT_DATA => self.data_pins = value,
+            PORT_CONTROL => self.set_control_pins(value),
+
+            _ => {}
+        }
+    }
+
+    fn device_id(&self) -> DeviceId {
+        DeviceId::CharLcd
+    }
+}
+
+impl AddressableDeviceTrait for CharLcd {
+    fn size(&self) -> u16 {
+        DeviceId::fixed_size(&DeviceId::CharLcd).unwrap()
+    }
+
+    fn read_unchecked(&self, offset: u16) -> u8 {
+        if offset == REG_INSTRUCTION {
+            self.status()
+        } else {
+            self.read_data()
+        }
+    }
+
+    fn peek_unchecked(&self, offset: u16) -> u8 {
+        if offset == REG_INSTRUCTION {
+            self.status()
+        } else {
+            self.peek_data()
+        }
+    }
+
+    fn write_unchecked(&mut self, offset: u16, value: u8) {
+        self.write(offset != REG_INSTRUCTION, value);
+    }
+}
@@ -0,0 +1,70 @@
+use super::super::{DeviceTrait, AddressableDeviceTrait, DeviceId};
+
+pub const PORT_BUTTONS: u8 = 0;
+
+/// Single byte, read only port with the state of a joystick/gamepad, a bit per button (set if pressed):
+///
+///  bit 0 up, 1 down, 2 left, 3 right, 4 A, 5 B, 6 select, 7 start
+///
+/// The value is also available on IO port `PORT_BUTTONS`, to wire it to a VIA.
+/// The state comes from the host, see `Self::set_buttons` and `Self::set_button`.
+pub struct Joystick {
+    buttons: u8,
+}
+
+impl Joystick {
+    pub fn new() -> Self {
+        Joystick {
+            buttons: 0,
+        }
+    }
+
+    /// Replaces the state of all the buttons.
+    pub fn set_buttons(&mut self, buttons: u8) {
+        self.buttons = buttons;
+    }
+
+    /// Presses or releases the buttons in `mask`.
+    pub fn set_button(&mut self, mask: u8, pressed: bool) {
+        if pressed {
+            self.buttons |= mask;
+        } else {
+            self.buttons &= !mask;
+        }
+    }
+}
+
+impl DeviceTrait for Joystick {
+    fn reset_system(&mut self) {
+        // the buttons are held by the host, so they survive a reset
+    }
+
+    fn reset_hard(&mut self) {
+        self.buttons = 0;
+    }
+
+    fn port_output(&self, port: u8) -> Option<u8> {
+        match port {
+            PORT_BUTTONS => Some(self.buttons),
+
+            _ => None,
+        }
+    }
+
+    fn device_id(&self) -> DeviceId {
+        DeviceId::Joystick
+    }
+}
+
+impl AddressableDeviceTrait for Joystick {
+    fn size(&self) -> u16 {
+        DeviceId::fixed_size(&DeviceId::Joystick).unwrap()
+    }
+
+    fn read_unchecked(&self, _offset: u16) -> u8 {
+        self.buttons
+    }
+
+    fn write_unchecked(&mut self, _offset: u16, _value: u8) {
+    }
+}
@@ -0,0 +1,55 @@
+use super::super::{DeviceTrait, AddressableDeviceTrait, DeviceId};
+
+/// Single byte holding the code of the last key pressed, like the easy6502 $FF location.
+///
+/// The key is set by the host (see `Self::set_key`), and stays there until the next key or until the
+/// program overwrites it (usually with 0, to mark it as handled).
+pub struct KeyLatch {
+    key: u8,
+}
+
+impl KeyLatch {
+    pub fn new() -> Self {
+        KeyLatch {
+            key: 0,
+        }
+    }
+
+    pub fn set_key(&mut self, key: u8) {
+        self.key = key;
+    }
+}
+
+impl DeviceTrait for KeyLatch {
+    fn reset_system(&mut self) {
+        self.key = 0;
+    }
+
+    fn reset_hard(&mut self) {
+        self.reset_system();
+    }
+
+    fn device_id(&self) -> DeviceId {
+        DeviceId::KeyLatch
+    }
+}
+
+impl AddressableDeviceTrait for KeyLatch {
+    fn size(&self) -> u16 {
+        DeviceId::fixed_size(&DeviceId::KeyLatch).unwrap()
+    }
+
+    fn read_unchecked(&self, _offset: u16) -> u8 {
+        self.key
+    }
+
+    fn write_unchecked(&mut self, _offset: u16, value: u8) {
+        self.key = value;
+    }
+
+    fn poke_unchecked(&mut self, _offset: u16, value: u8) -> bool {
+        self.key = value;
+
+        true
+    }
+}
@@ -0,0 +1,114 @@
+use super::super::{DeviceTrait, AddressableDeviceTrait, DeviceId};
+
+// 8x8 keyboard matrix, scanned by the program like the real hardware.
+//
+// Register layout:
+//
+//  +0  row select, each set bit selects a row (read back as written)
+//  +1  columns, read only: bit N is set if the key at column N is pressed in any of the selected rows
+//
+// The same scan can be done through the IO ports, `PORT_ROWS` takes the row select as input
+// and `PORT_COLUMNS` outputs the columns, so the matrix can be wired to a VIA.
+//
+// The key state comes from the host, see `Self::set_key`.
+
+pub const PORT_ROWS: u8 = 0;
+pub const PORT_COLUMNS: u8 = 1;
+
+const REG_ROW_SELECT: u16 = 0;
+
+pub const MATRIX_SIZE: u8 = 8;
+
+pub struct KeyboardMatrix {
+    /// Pressed keys, a bit per column for each row.
+    keys: [u8; MATRIX_SIZE as usize],
+
+    row_select: u8,
+}
+
+impl KeyboardMatrix {
+    pub fn new() -> Self {
+        KeyboardMatrix {
+            keys: [0; MATRIX_SIZE as usize],
+
+            row_select: 0,
+        }
+    }
+
+    /// Presses or releases the key at `row`, `column`. Out of range positions are ignored.
+    pub fn set_key(&mut self, row: u8, column: u8, pressed: bool) {
+        if row >= MATRIX_SIZE || column >= MATRIX_SIZE {
+            return;
+        }
+
+        let keys = &mut self.keys[row as usize];
+
+        if pressed {
+            *keys |= 1 << column;
+        } else {
+            *keys &= !(1 << column);
+        }
+    }
+
+    pub fn release_all(&mut self) {
+        self.keys = [0; MATRIX_SIZE as usize];
+    }
+
+    fn columns(&self) -> u8 {
+        self.keys.iter()
+            .enumerate()
+            .filter(|(row, _)| (self.row_select & (1 << row)) != 0)
+            .fold(0, |columns, (_, keys)| columns | keys)
+    }
+}
+
+impl DeviceTrait for KeyboardMatrix {
+    fn reset_system(&mut self) {
+        // the keys are held by the host, so they survive a reset
+        self.row_select = 0;
+    }
+
+    fn reset_hard(&mut self) {
+        self.release_all();
+
+        self.reset_system();
+    }
+
+    fn port_output(&self, port: u8) -> Option<u8> {
+        match port {
+            PORT_COLUMNS => Some(self.columns()),
+
+            _ => None,
+        }
+    }
+
+    fn set_port_input(&mut self, port: u8, value: u8) {
+        if port == PORT_ROWS {
+            self.row_select = value;
+        }
+    }
+
+    fn device_id(&self) -> DeviceId {
+        DeviceId::KeyboardMatrix
+    }
+}
+
+impl AddressableDeviceTrait for KeyboardMatrix {
+    fn size(&self) -> u16 {
+        DeviceId::fixed_size(&DeviceId::KeyboardMatrix).unwrap()
+    }
+
+    fn read_unchecked(&self, offset: u16) -> u8 {
+        if offset == REG_ROW_SELECT {
+            self.row_select
+        } else {
+            self.columns()
+        }
+    }
+
+    fn write_unchecked(&mut self, offset: u16, value: u8) {
+        if offset == REG_ROW_SELECT {
+            self.row_select = value;
+        }
+    }
+}
@@ -1,5 +1,31 @@
 mod screen;
+pub mod char_lcd;
 mod ascii_io_buffer;
+mod serial_backend;
+mod acia;
+pub mod keyboard_matrix;
+pub mod joystick;
+mod key_latch;
+mod semihost;
+pub mod via;
+mod pia;
+mod sound;
+mod prng;
+mod rtc;
 
 pub use screen::PixelScreen;
+pub use char_lcd::CharLcd;
 pub use ascii_io_buffer::AsciiIOBuffer;
+pub use serial_backend::{SerialBackend, BufferSerial};
+#[cfg(not(target_arch = "wasm32"))]
+pub use serial_backend::{StdioSerial, FileSerial, TcpSerial};
+pub use acia::Acia;
+pub use keyboard_matrix::KeyboardMatrix;
+pub use joystick::Joystick;
+pub use key_latch::KeyLatch;
+pub use semihost::Semihost;
+pub use via::Via;
+pub use pia::Pia;
+pub use sound::SoundGenerator;
+pub use prng::Prng;
+pub use rtc::RealTimeClock;
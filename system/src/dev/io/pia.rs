@@ -0,0 +1,180 @@
+use std::cell::Cell;
+use std::collections::VecDeque;
+
+use js_sys::Map;
+
+use super::super::{DeviceTrait, AddressableDeviceTrait, DeviceId, utils};
+
+// 6821 Peripheral Interface Adapter, wired like the Apple-1 keyboard and display.
+//
+// Register layout:
+//
+//  +0 KBD    port A (or DDRA): keyboard, read only, bit 7 is always set
+//  +1 KBDCR  control register A, bit 7 is set when a key is available, reading KBD clears it
+//  +2 DSP    port B (or DDRB): writing sends bits 0-6 to the display, bit 7 reads as 0 (display ready)
+//  +3 DSPCR  control register B
+//
+// Like in the real chip, bit 2 of the control registers selects between the port (set) and its
+// data direction register (clear), so the usual Woz monitor initialization works as is.
+//
+// The keyboard only has upper case letters and sends CR as the end of line, the host input is
+// converted accordingly. The display output is kept as ASCII, with CR converted to '\n'.
+
+const REG_KBD: u16 = 0;
+const REG_KBDCR: u16 = 1;
+const REG_DSP: u16 = 2;
+
+const CR_PORT_SELECT: u8 = 1 << 2;
+const CR_IRQ1_FLAG: u8 = 1 << 7;
+
+// Bits 6-7 of the control registers are read only flags.
+const CR_WRITABLE_MASK: u8 = 0b0011_1111;
+
+const KEY_STROBE: u8 = 1 << 7;
+const CARRIAGE_RETURN: u8 = 0x0D;
+
+pub struct Pia {
+    ddr_a: u8,
+    ddr_b: u8,
+
+    // reads only get a shared reference, and reading KBD clears the key flag.
+    control_a: Cell<u8>,
+    control_b: u8,
+
+    key: u8,
+
+    input: VecDeque<u8>,
+    output: Vec<u8>,
+}
+
+impl Pia {
+    pub fn new() -> Self {
+        Pia {
+            ddr_a: 0,
+            ddr_b: 0,
+
+            control_a: Cell::new(0),
+            control_b: 0,
+
+            key: 0,
+
+            input: VecDeque::new(),
+            output: Vec::new(),
+        }
+    }
+
+    /// Queues keys typed by the host, they are given to the program one at a time.
+    pub fn push_input(&mut self, data: &[u8]) {
+        let keys = data.iter().map(|key| match key {
+            b'\n' => CARRIAGE_RETURN,
+            key => key.to_ascii_uppercase() & 0x7F,
+        });
+
+        self.input.extend(keys);
+    }
+
+    /// Returns (and forgets) everything sent to the display so far.
+    pub fn take_output(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.output)
+    }
+
+    fn key_available(&self) -> bool {
+        (self.control_a.get() & CR_IRQ1_FLAG) != 0
+    }
+}
+
+impl DeviceTrait for Pia {
+    fn tick(&mut self) {
+        if !self.key_available() {
+            if let Some(key) = self.input.pop_front() {
+                self.key = key;
+                self.control_a.set(self.control_a.get() | CR_IRQ1_FLAG);
+            }
+        }
+    }
+
+    fn reset_system(&mut self) {
+        self.ddr_a = 0;
+        self.ddr_b = 0;
+
+        self.control_a.set(0);
+        self.control_b = 0;
+
+        self.key = 0;
+    }
+
+    fn reset_hard(&mut self) {
+        self.reset_system();
+
+        self.input.clear();
+        self.output.clear();
+    }
+
+    fn update_widget(&mut self, pkg: &Map) {
+        utils::js_map_add_entry_f64(pkg, "control_a", self.control_a.get());
+        utils::js_map_add_entry_f64(pkg, "control_b", self.control_b);
+    }
+
+    fn device_id(&self) -> DeviceId {
+        DeviceId::Pia
+    }
+}
+
+impl AddressableDeviceTrait for Pia {
+    fn size(&self) -> u16 {
+        DeviceId::fixed_size(&DeviceId::Pia).unwrap()
+    }
+
+    fn read_unchecked(&self, offset: u16) -> u8 {
+        match offset {
+            REG_KBD if (self.control_a.get() & CR_PORT_SELECT) == 0 => self.ddr_a,
+
+            REG_KBD => {
+                self.control_a.set(self.control_a.get() & !CR_IRQ1_FLAG);
+
+                self.key | KEY_STROBE
+            }
+
+            REG_KBDCR => self.control_a.get(),
+
+            REG_DSP if (self.control_b & CR_PORT_SELECT) == 0 => self.ddr_b,
+
+            // the display is always ready
+            REG_DSP => 0,
+
+            _ => self.control_b,
+        }
+    }
+
+    fn peek_unchecked(&self, offset: u16) -> u8 {
+        match offset {
+            REG_KBD if (self.control_a.get() & CR_PORT_SELECT) != 0 => self.key | KEY_STROBE,
+
+            _ => self.read_unchecked(offset),
+        }
+    }
+
+    fn write_unchecked(&mut self, offset: u16, value: u8) {
+        match offset {
+            REG_KBD if (self.control_a.get() & CR_PORT_SELECT) == 0 => self.ddr_a = value,
+
+            // the keyboard is an input only port
+            REG_KBD => {}
+
+            REG_KBDCR => {
+                let flags = self.control_a.get() & !CR_WRITABLE_MASK;
+
+                self.control_a.set(flags | (value & CR_WRITABLE_MASK));
+            }
+
+            REG_DSP if (self.control_b & CR_PORT_SELECT) == 0 => self.ddr_b = value,
+
+            REG_DSP => match value & 0x7F {
+                CARRIAGE_RETURN => self.output.push(b'\n'),
+                char => self.output.push(char),
+            },
+
+            _ => self.control_b = value & CR_WRITABLE_MASK,
+        }
+    }
+}
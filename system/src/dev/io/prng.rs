@@ -0,0 +1,98 @@
+use std::cell::Cell;
+
+use super::super::{DeviceTrait, AddressableDeviceTrait, DeviceId, utils};
+
+/// Single byte port, every read returns a new pseudo random byte.
+///
+/// Writing to the port (or calling `Self::seed`) switches the generator to deterministic mode,
+/// where the sequence only depends on the seed and is restarted on every system reset.
+/// By default the generator is seeded from the host clock.
+pub struct Prng {
+    // reads only get a shared reference
+    state: Cell<u32>,
+
+    fixed_seed: Option<u32>,
+}
+
+impl Prng {
+    pub fn new() -> Self {
+        let mut tmp = Prng {
+            state: Cell::new(1),
+
+            fixed_seed: None,
+        };
+
+        tmp.reset_system();
+
+        tmp
+    }
+
+    /// Seeds the generator and switches it to deterministic mode.
+    pub fn seed(&mut self, seed: u32) {
+        self.fixed_seed = Some(seed);
+
+        self.restart();
+    }
+
+    fn restart(&mut self) {
+        let seed = self.fixed_seed
+            .unwrap_or_else(|| utils::host_time_millis() as u32);
+
+        // xorshift gets stuck on 0
+        self.state.set(if seed == 0 { 0x2545_F491 } else { seed });
+    }
+
+    fn next_state(&self) -> u32 {
+        // xorshift32
+        let mut x = self.state.get();
+
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+
+        x
+    }
+
+    fn next_byte(&self) -> u8 {
+        let x = self.next_state();
+
+        self.state.set(x);
+
+        (x >> 24) as u8
+    }
+}
+
+impl DeviceTrait for Prng {
+    fn reset_system(&mut self) {
+        self.restart();
+    }
+
+    fn reset_hard(&mut self) {
+        self.fixed_seed = None;
+
+        self.restart();
+    }
+
+    fn device_id(&self) -> DeviceId {
+        DeviceId::Prng
+    }
+}
+
+impl AddressableDeviceTrait for Prng {
+    fn size(&self) -> u16 {
+        DeviceId::fixed_size(&DeviceId::Prng).unwrap()
+    }
+
+    fn read_unchecked(&self, _offset: u16) -> u8 {
+        self.next_byte()
+    }
+
+    /// Returns the byte the next read will return.
+    fn peek_unchecked(&self, _offset: u16) -> u8 {
+        (self.next_state() >> 24) as u8
+    }
+
+    fn write_unchecked(&mut self, _offset: u16, value: u8) {
+        self.seed(value as u32);
+    }
+}
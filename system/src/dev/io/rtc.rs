@@ -0,0 +1,149 @@
+use super::super::{DeviceTrait, AddressableDeviceTrait, DeviceId, utils, CLOCK_HZ};
+
+// Register layout:
+//
+//  +0      latch, writing any value copies the current date/time and cycle counter
+//          into the other registers, so multi byte values can be read consistently.
+//  +1      seconds (0-59)
+//  +2      minutes (0-59)
+//  +3      hours   (0-23)
+//  +4      day     (1-31)
+//  +5      month   (1-12)
+//  +6..=7  year    (little endian)
+//  +8..=11 cycle counter (little endian, low 32 bits of the cycles since the last reset)
+//
+// All values are in UTC.
+
+const REG_LATCH: usize = 0;
+const REG_SECONDS: usize = 1;
+const REG_MINUTES: usize = 2;
+const REG_HOURS: usize = 3;
+const REG_DAY: usize = 4;
+const REG_MONTH: usize = 5;
+const REG_YEAR: usize = 6;
+const REG_CYCLES: usize = 8;
+
+// keep in sync with DeviceId::fixed_size
+const REGISTERS_SIZE: usize = 12;
+
+pub struct RealTimeClock {
+    latched: [u8; REGISTERS_SIZE],
+
+    cycles: u64,
+
+    /// If set, the time is not taken from the host, instead it starts at this value (unix seconds)
+    /// and advances with the system ticks. This makes the device deterministic.
+    emulated_epoch: Option<u64>,
+}
+
+impl RealTimeClock {
+    pub fn new() -> Self {
+        let mut tmp = RealTimeClock {
+            latched: [0; REGISTERS_SIZE],
+
+            cycles: 0,
+
+            emulated_epoch: None,
+        };
+
+        tmp.latch();
+
+        tmp
+    }
+
+    /// Makes the clock start at `unix_seconds` and advance with the system ticks instead of using the host time.
+    pub fn set_time(&mut self, unix_seconds: u64) {
+        self.emulated_epoch = Some(unix_seconds);
+
+        self.latch();
+    }
+
+    /// Goes back to using the host time.
+    pub fn use_host_time(&mut self) {
+        self.emulated_epoch = None;
+
+        self.latch();
+    }
+
+    fn now_unix_seconds(&self) -> u64 {
+        match self.emulated_epoch {
+            Some(epoch) => epoch + self.cycles / CLOCK_HZ as u64,
+            None => utils::host_time_millis() / 1000,
+        }
+    }
+
+    fn latch(&mut self) {
+        let now = self.now_unix_seconds();
+
+        let days = now / 86_400;
+        let secs_of_day = now % 86_400;
+
+        let (year, month, day) = civil_from_days(days as i64);
+
+        self.latched[REG_SECONDS] = (secs_of_day % 60) as u8;
+        self.latched[REG_MINUTES] = (secs_of_day / 60 % 60) as u8;
+        self.latched[REG_HOURS] = (secs_of_day / 3600) as u8;
+
+        self.latched[REG_DAY] = day;
+        self.latched[REG_MONTH] = month;
+        self.latched[REG_YEAR..REG_YEAR + 2].copy_from_slice(&(year as u16).to_le_bytes());
+
+        self.latched[REG_CYCLES..REG_CYCLES + 4].copy_from_slice(&(self.cycles as u32).to_le_bytes());
+    }
+}
+
+/// Converts days since the unix epoch into a (year, month, day) date.
+///
+/// http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097); // [0, 146096]
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365; // [0, 399]
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100); // [0, 365]
+    let mp = (5 * doy + 2) / 153; // [0, 11]
+
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u8;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+impl DeviceTrait for RealTimeClock {
+    fn tick(&mut self) {
+        self.cycles += 1;
+    }
+
+    fn reset_system(&mut self) {
+        self.cycles = 0;
+
+        self.latch();
+    }
+
+    fn reset_hard(&mut self) {
+        self.emulated_epoch = None;
+
+        self.reset_system();
+    }
+
+    fn device_id(&self) -> DeviceId {
+        DeviceId::RealTimeClock
+    }
+}
+
+impl AddressableDeviceTrait for RealTimeClock {
+    fn size(&self) -> u16 {
+        DeviceId::fixed_size(&DeviceId::RealTimeClock).unwrap()
+    }
+
+    fn read_unchecked(&self, offset: u16) -> u8 {
+        self.latched[offset as usize]
+    }
+
+    fn write_unchecked(&mut self, offset: u16, _value: u8) {
+        if offset as usize == REG_LATCH {
+            self.latch();
+        }
+    }
+}
@@ -264,57 +264,121 @@ const COLOR_PALETTE: [&'static str; 256] = [
     "#eeeeee",
 ];
 
-const BACKGROUND_COLOR: &'static str = COLOR_PALETTE[0];
+/// easy6502 colors, only the low nibble of the value is used.
+const EASY6502_PALETTE: [&'static str; 16] = [
+    "#000000",
+    "#ffffff",
+    "#880000",
+    "#aaffee",
+    "#cc44cc",
+    "#00cc55",
+    "#0000aa",
+    "#eeee77",
+    "#dd8855",
+    "#664400",
+    "#ff7777",
+    "#333333",
+    "#777777",
+    "#aaff66",
+    "#0088ff",
+    "#bbbbbb",
+];
 
 // keep in sync with DeviceID::fixed_size
 const DEFAULT_WIDTH: u16 = 50;
 const DEFAULT_HEIGHT: u16 = 50;
 
+const COLOR_SCREEN_WIDTH: u16 = 32;
+const COLOR_SCREEN_HEIGHT: u16 = 32;
+
 // scaling prevents blurring
 const SCALE_FACTOR: u16 = 20;
 
-const SCALED_WIDTH: u16 = DEFAULT_WIDTH * SCALE_FACTOR;
-const SCALED_HEIGHT: u16 = DEFAULT_HEIGHT * SCALE_FACTOR;
-
+/// Screen with a byte per pixel, in rows from the top left corner.
+///
+/// `PixelScreen::new` creates a 50x50 screen with 256 colors, and `PixelScreen::color_screen`
+/// a 32x32 screen with 16 colors that behaves like the easy6502 one.
+///
+/// The canvas is only created when a widget is set up, so the screen also works outside of the
+/// browser (without drawing anything).
 pub struct PixelScreen {
-    canvas: HtmlCanvasElement,
-    canvas_context: CanvasRenderingContext2d,
+    canvas: Option<(HtmlCanvasElement, CanvasRenderingContext2d)>,
+
+    width: u16,
+    height: u16,
+    palette: &'static [&'static str],
+
+    id: DeviceId,
+
+    // kept so programs can read back the screen
+    pixels: Vec<u8>,
 }
 
 impl PixelScreen {
     pub fn new() -> Self {
-        let document = web_sys::window().unwrap().document().unwrap();
+        Self::with_layout(DeviceId::PixelScreen, DEFAULT_WIDTH, DEFAULT_HEIGHT, &COLOR_PALETTE)
+    }
+
+    pub fn color_screen() -> Self {
+        Self::with_layout(DeviceId::ColorScreen, COLOR_SCREEN_WIDTH, COLOR_SCREEN_HEIGHT, &EASY6502_PALETTE)
+    }
+
+    fn with_layout(id: DeviceId, width: u16, height: u16, palette: &'static [&'static str]) -> Self {
+        PixelScreen {
+            canvas: None,
 
-        let canvas = document.create_element("canvas").unwrap();
-        let canvas = canvas
-            .dyn_into::<web_sys::HtmlCanvasElement>()
-            .unwrap();
+            width,
+            height,
+            palette,
 
-        canvas.set_width(SCALED_WIDTH as u32);
-        canvas.set_height(SCALED_HEIGHT as u32);
+            id,
+
+            pixels: vec![0; (width * height) as usize],
+        }
+    }
+
+    fn create_canvas(&self) -> Option<(HtmlCanvasElement, CanvasRenderingContext2d)> {
+        let document = web_sys::window()?.document()?;
+
+        let canvas = document.create_element("canvas").ok()?
+            .dyn_into::<HtmlCanvasElement>()
+            .ok()?;
+
+        canvas.set_width((self.width * SCALE_FACTOR) as u32);
+        canvas.set_height((self.height * SCALE_FACTOR) as u32);
 
         let canvas_context = canvas
-            .get_context("2d")
-            .unwrap()
-            .unwrap()
-            .dyn_into::<web_sys::CanvasRenderingContext2d>()
-            .unwrap();
+            .get_context("2d").ok()??
+            .dyn_into::<CanvasRenderingContext2d>()
+            .ok()?;
+
+        Some((canvas, canvas_context))
+    }
 
-        let mut tmp = PixelScreen {
-            canvas,
-            canvas_context,
-        };
+    fn draw_pixel(&self, offset: u16) {
+        if let Some((_, canvas_context)) = &self.canvas {
+            let x = (offset % self.width) * SCALE_FACTOR;
+            let y = (offset / self.width) * SCALE_FACTOR;
 
-        tmp.reset_system();
+            let color = self.palette[self.pixels[offset as usize] as usize % self.palette.len()];
 
-        tmp
+            canvas_context.set_fill_style(&JsValue::from_str(color));
+            canvas_context.fill_rect(x as f64, y as f64, SCALE_FACTOR.into(), SCALE_FACTOR.into());
+        }
     }
 }
 
 impl DeviceTrait for PixelScreen {
     fn reset_system(&mut self) {
-        self.canvas_context.set_fill_style(&JsValue::from_str(BACKGROUND_COLOR));
-        self.canvas_context.fill_rect(0.0, 0.0, SCALED_WIDTH as f64, SCALED_HEIGHT as f64);
+        self.pixels.iter_mut().for_each(|pixel| *pixel = 0);
+
+        if let Some((_, canvas_context)) = &self.canvas {
+            canvas_context.set_fill_style(&JsValue::from_str(self.palette[0]));
+            canvas_context.fill_rect(
+                0.0, 0.0,
+                (self.width * SCALE_FACTOR) as f64, (self.height * SCALE_FACTOR) as f64,
+            );
+        }
     }
 
     fn reset_hard(&mut self) {
@@ -322,30 +386,42 @@ impl DeviceTrait for PixelScreen {
     }
 
     fn setup_widget(&mut self, pkg: &Map) {
-        pkg.set(&JsValue::from_str("canvas"), &self.canvas);
+        if self.canvas.is_none() {
+            self.canvas = self.create_canvas();
+
+            for offset in 0..self.size() {
+                self.draw_pixel(offset);
+            }
+        }
+
+        if let Some((canvas, _)) = &self.canvas {
+            pkg.set(&JsValue::from_str("canvas"), canvas);
+        }
     }
 
     fn device_id(&self) -> DeviceId {
-        DeviceId::PixelScreen
+        self.id
     }
 }
 
 impl AddressableDeviceTrait for PixelScreen {
     fn size(&self) -> u16 {
-        DeviceId::fixed_size(&DeviceId::PixelScreen).unwrap()
+        self.width * self.height
     }
 
-    fn read_unchecked(&self, _offset: u16) -> u8 {
-        0 //TODO
+    fn read_unchecked(&self, offset: u16) -> u8 {
+        self.pixels[offset as usize]
     }
 
     fn write_unchecked(&mut self, offset: u16, value: u8) {
-        let x = (offset % 50) * SCALE_FACTOR;
-        let y = (offset / 50) * SCALE_FACTOR;
+        self.pixels[offset as usize] = value;
+
+        self.draw_pixel(offset);
+    }
 
-        let color = COLOR_PALETTE[value as usize];
+    fn poke_unchecked(&mut self, offset: u16, value: u8) -> bool {
+        self.write_unchecked(offset, value);
 
-        self.canvas_context.set_fill_style(&JsValue::from_str(color));
-        self.canvas_context.fill_rect(x as f64, y as f64, SCALE_FACTOR.into(), SCALE_FACTOR.into());
+        true
     }
 }
@@ -0,0 +1,318 @@
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
+use std::rc::Rc;
+
+use super::super::{DeviceTrait, AddressableDeviceTrait, DeviceId};
+
+// Host services ("semihosting"), for programs that run as tests or tools.
+//
+// Register layout:
+//
+//  +0 command  write: runs one of the CMD_*, read: status of the last command (see STATUS_*)
+//  +1 data     write: appends a byte to the buffer, read: takes a byte from the input (0 if empty)
+//  +2 arg lo   argument of the commands that need a number
+//  +3 arg hi
+//  +4 result   32 bits little endian, set by the commands that return something
+//
+// Strings and file contents are sent by writing them to the data register before the command,
+// the commands that use the buffer clear it.
+//
+// A command runs in the first cycle after the writes to the command register, so only the last
+// of the writes of a read-modify-write instruction (the old value, then the new one) runs.
+
+const REG_COMMAND: u16 = 0;
+const REG_DATA: u16 = 1;
+const REG_ARG_LO: u16 = 2;
+const REG_ARG_HI: u16 = 3;
+const REG_RESULT: u16 = 4;
+
+/// Prints the argument as an unsigned decimal number.
+const CMD_PRINT_NUMBER: u8 = 0x01;
+/// Prints the buffer as text.
+const CMD_PRINT_STRING: u8 = 0x02;
+/// Stops `System::run`, the low byte of the argument is the exit code.
+const CMD_EXIT: u8 = 0x03;
+/// Uses the buffer as the name of the file for the next file commands.
+const CMD_SET_FILE_NAME: u8 = 0x04;
+/// Appends the contents of the file to the input, the result is its length.
+const CMD_READ_FILE: u8 = 0x05;
+/// Replaces the contents of the file with the buffer.
+const CMD_WRITE_FILE: u8 = 0x06;
+/// Sets the result to the cycles since the last reset (see `System::cycles`), the low 32 bits.
+const CMD_GET_CYCLES: u8 = 0x07;
+
+const STATUS_OK: u8 = 0;
+const STATUS_UNKNOWN_COMMAND: u8 = 1;
+const STATUS_BAD_FILE_NAME: u8 = 2;
+const STATUS_FILE_ERROR: u8 = 3;
+
+/// File names can't reach outside the sandbox, so they are limited to a single plain name.
+fn is_valid_file_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-')
+}
+
+pub struct Semihost {
+    command_status: u8,
+
+    /// Written to the command register, it runs once the writes stop.
+    pending_command: Option<u8>,
+    command_written: bool,
+
+    arg: u16,
+    result: u32,
+
+    buffer: Vec<u8>,
+
+    // reads only get a shared reference, and reading the data register takes the byte.
+    input: RefCell<VecDeque<u8>>,
+
+    output: Vec<u8>,
+
+    file_name: Option<String>,
+
+    /// Files used when there is no sandbox directory.
+    files: HashMap<String, Vec<u8>>,
+
+    #[cfg(not(target_arch = "wasm32"))]
+    sandbox: Option<std::path::PathBuf>,
+
+    exit_code: Option<u8>,
+
+    /// Cycles of the system, see `Self::set_clock`.
+    clock: Option<Rc<Cell<u64>>>,
+}
+
+impl Semihost {
+    pub fn new() -> Self {
+        Semihost {
+            command_status: STATUS_OK,
+
+            pending_command: None,
+            command_written: false,
+
+            arg: 0,
+            result: 0,
+
+            buffer: Vec::new(),
+            input: RefCell::new(VecDeque::new()),
+            output: Vec::new(),
+
+            file_name: None,
+            files: HashMap::new(),
+
+            #[cfg(not(target_arch = "wasm32"))]
+            sandbox: None,
+
+            exit_code: None,
+
+            clock: None,
+        }
+    }
+
+    /// Gives the device the cycle count of the system, without it the cycles are always 0.
+    pub fn set_clock(&mut self, clock: Rc<Cell<u64>>) {
+        self.clock = Some(clock);
+    }
+
+    /// Removes and returns everything printed so far.
+    pub fn take_output(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.output)
+    }
+
+    /// Adds (or replaces) a file available to the program, when there is no sandbox directory.
+    pub fn set_file(&mut self, name: &str, data: &[u8]) {
+        self.files.insert(name.to_string(), data.to_vec());
+    }
+
+    /// Returns a file written by the program, when there is no sandbox directory.
+    pub fn file(&self, name: &str) -> Option<&[u8]> {
+        self.files.get(name).map(|data| data.as_slice())
+    }
+
+    /// Makes the file commands use the files in `dir` instead of the ones in memory.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_sandbox(&mut self, dir: Option<std::path::PathBuf>) {
+        self.sandbox = dir;
+    }
+
+    fn read_file(&self, name: &str) -> Option<Vec<u8>> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Some(dir) = &self.sandbox {
+                return std::fs::read(dir.join(name)).ok();
+            }
+        }
+
+        self.files.get(name).cloned()
+    }
+
+    fn write_file(&mut self, name: &str, data: Vec<u8>) -> bool {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Some(dir) = &self.sandbox {
+                return std::fs::write(dir.join(name), data).is_ok();
+            }
+        }
+
+        self.files.insert(name.to_string(), data);
+
+        true
+    }
+
+    fn run_command(&mut self, command: u8) -> u8 {
+        match command {
+            CMD_PRINT_NUMBER => {
+                self.output.extend(self.arg.to_string().bytes());
+            }
+
+            CMD_PRINT_STRING => {
+                let text = std::mem::take(&mut self.buffer);
+
+                self.output.extend(text);
+            }
+
+            CMD_EXIT => {
+                self.exit_code = Some(self.arg as u8);
+            }
+
+            CMD_SET_FILE_NAME => {
+                let name = String::from_utf8(std::mem::take(&mut self.buffer)).ok()
+                    .filter(|name| is_valid_file_name(name));
+
+                let is_valid = name.is_some();
+                self.file_name = name;
+
+                if !is_valid {
+                    return STATUS_BAD_FILE_NAME;
+                }
+            }
+
+            CMD_READ_FILE => {
+                let name = match &self.file_name {
+                    Some(name) => name,
+                    None => return STATUS_BAD_FILE_NAME,
+                };
+
+                match self.read_file(name) {
+                    Some(data) => {
+                        self.result = data.len() as u32;
+                        self.input.get_mut().extend(data);
+                    }
+
+                    None => return STATUS_FILE_ERROR,
+                }
+            }
+
+            CMD_WRITE_FILE => {
+                let name = match self.file_name.clone() {
+                    Some(name) => name,
+                    None => return STATUS_BAD_FILE_NAME,
+                };
+
+                let data = std::mem::take(&mut self.buffer);
+
+                if !self.write_file(&name, data) {
+                    return STATUS_FILE_ERROR;
+                }
+            }
+
+            CMD_GET_CYCLES => {
+                self.result = self.clock.as_ref().map_or(0, |clock| clock.get() as u32);
+            }
+
+            _ => return STATUS_UNKNOWN_COMMAND,
+        }
+
+        STATUS_OK
+    }
+}
+
+impl Default for Semihost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeviceTrait for Semihost {
+    fn tick(&mut self) {
+        if self.command_written {
+            self.command_written = false;
+        } else if let Some(command) = self.pending_command.take() {
+            self.command_status = self.run_command(command);
+        }
+    }
+
+    // the files are kept, they belong to the host
+    fn reset_system(&mut self) {
+        self.command_status = STATUS_OK;
+
+        self.pending_command = None;
+        self.command_written = false;
+
+        self.arg = 0;
+        self.result = 0;
+
+        self.buffer.clear();
+        self.input.get_mut().clear();
+        self.output.clear();
+
+        self.file_name = None;
+
+        self.exit_code = None;
+    }
+
+    fn reset_hard(&mut self) {
+        self.reset_system();
+    }
+
+    fn take_exit_code(&mut self) -> Option<u8> {
+        self.exit_code.take()
+    }
+
+    fn device_id(&self) -> DeviceId {
+        DeviceId::Semihost
+    }
+}
+
+impl AddressableDeviceTrait for Semihost {
+    fn size(&self) -> u16 {
+        DeviceId::Semihost.fixed_size().unwrap()
+    }
+
+    fn read_unchecked(&self, offset: u16) -> u8 {
+        match offset {
+            REG_DATA => self.input.borrow_mut().pop_front().unwrap_or(0),
+
+            _ => self.peek_unchecked(offset),
+        }
+    }
+
+    fn peek_unchecked(&self, offset: u16) -> u8 {
+        match offset {
+            REG_COMMAND => self.command_status,
+            REG_DATA => self.input.borrow().front().copied().unwrap_or(0),
+            REG_ARG_LO => self.arg as u8,
+            REG_ARG_HI => (self.arg >> 8) as u8,
+
+            _ => self.result.to_le_bytes()[(offset - REG_RESULT) as usize],
+        }
+    }
+
+    fn write_unchecked(&mut self, offset: u16, value: u8) {
+        match offset {
+            REG_COMMAND => {
+                self.pending_command = Some(value);
+                self.command_written = true;
+            }
+
+            REG_DATA => self.buffer.push(value),
+            REG_ARG_LO => self.arg = (self.arg & 0xFF00) | value as u16,
+            REG_ARG_HI => self.arg = (self.arg & 0x00FF) | ((value as u16) << 8),
+
+            // the result is read only
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use std::any::Any;
+use std::collections::VecDeque;
+
+/// Host side of a serial device, where the transmitted bytes go and the received bytes come from.
+///
+/// `Any` allows getting the concrete backend back, see `Acia::backend_as_mut`.
+pub trait SerialBackend: Any {
+    /// Returns the next byte sent by the host, if there is any. Must not block.
+    fn read_byte(&mut self) -> Option<u8>;
+
+    /// Sends `value` to the host.
+    fn write_byte(&mut self, value: u8);
+}
+
+/// Keeps everything in memory, the host pushes the input and takes the output.
+///
+/// This is the default backend, and the only one available in the browser.
+pub struct BufferSerial {
+    input: VecDeque<u8>,
+    output: Vec<u8>,
+}
+
+impl BufferSerial {
+    pub fn new() -> Self {
+        BufferSerial {
+            input: VecDeque::new(),
+            output: Vec::new(),
+        }
+    }
+
+    pub fn push_input(&mut self, data: &[u8]) {
+        self.input.extend(data);
+    }
+
+    /// Removes and returns everything transmitted so far.
+    pub fn take_output(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.output)
+    }
+}
+
+impl Default for BufferSerial {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SerialBackend for BufferSerial {
+    fn read_byte(&mut self) -> Option<u8> {
+        self.input.pop_front()
+    }
+
+    fn write_byte(&mut self, value: u8) {
+        self.output.push(value);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::{StdioSerial, FileSerial, TcpSerial};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use std::collections::VecDeque;
+    use std::fs::{self, File, OpenOptions};
+    use std::io::{self, Read, Write, ErrorKind};
+    use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+    use std::path::Path;
+    use std::sync::mpsc::{self, Receiver};
+    use std::thread;
+
+    use super::SerialBackend;
+
+    /// Connects the device to the process stdin and stdout.
+    pub struct StdioSerial {
+        // stdin reads block, so they are done in another thread.
+        input: Receiver<u8>,
+    }
+
+    impl StdioSerial {
+        pub fn new() -> Self {
+            let (sender, input) = mpsc::channel();
+
+            thread::spawn(move || {
+                let mut stdin = io::stdin();
+                let mut buf = [0_u8; 256];
+
+                while let Ok(read @ 1..=256) = stdin.read(&mut buf) {
+                    if buf[..read].iter().any(|byte| sender.send(*byte).is_err()) {
+                        break;
+                    }
+                }
+            });
+
+            StdioSerial {
+                input,
+            }
+        }
+    }
+
+    impl Default for StdioSerial {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl SerialBackend for StdioSerial {
+        fn read_byte(&mut self) -> Option<u8> {
+            self.input.try_recv().ok()
+        }
+
+        fn write_byte(&mut self, value: u8) {
+            let mut stdout = io::stdout();
+
+            // there is nothing useful to do if the host can't take the output
+            let _ = stdout.write_all(&[value]).and_then(|_| stdout.flush());
+        }
+    }
+
+    /// Reads the input from a file (all at once, when opened) and writes the output to another one.
+    pub struct FileSerial {
+        input: io::Bytes<io::Cursor<Vec<u8>>>,
+        output: Option<File>,
+    }
+
+    impl FileSerial {
+        /// Both files are optional, without an input nothing is received and without an output
+        /// everything transmitted is discarded. The output file is truncated.
+        pub fn open(input: Option<&Path>, output: Option<&Path>) -> io::Result<Self> {
+            let input_data = match input {
+                Some(path) => fs::read(path)?,
+                None => Vec::new(),
+            };
+
+            let output = match output {
+                Some(path) => Some(
+                    OpenOptions::new().write(true).create(true).truncate(true).open(path)?
+                ),
+
+                None => None,
+            };
+
+            Ok(FileSerial {
+                input: io::Cursor::new(input_data).bytes(),
+                output,
+            })
+        }
+    }
+
+    impl SerialBackend for FileSerial {
+        fn read_byte(&mut self) -> Option<u8> {
+            self.input.next().and_then(|byte| byte.ok())
+        }
+
+        fn write_byte(&mut self, value: u8) {
+            if let Some(file) = &mut self.output {
+                let _ = file.write_all(&[value]);
+            }
+        }
+    }
+
+    /// Bytes kept at most while the peer doesn't take them, the oldest are dropped.
+    const MAX_UNSENT: usize = 0x1_0000;
+
+    /// Exchanges the bytes with a TCP peer, either by connecting to it or by waiting for it to connect.
+    ///
+    /// When listening, a new client can connect after the previous one disconnects.
+    pub struct TcpSerial {
+        listener: Option<TcpListener>,
+        stream: Option<TcpStream>,
+
+        /// Transmitted bytes the socket couldn't take yet, sent on the next calls.
+        unsent: VecDeque<u8>,
+    }
+
+    impl TcpSerial {
+        pub fn connect<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+            let stream = TcpStream::connect(addr)?;
+            stream.set_nonblocking(true)?;
+
+            Ok(TcpSerial {
+                listener: None,
+                stream: Some(stream),
+
+                unsent: VecDeque::new(),
+            })
+        }
+
+        /// Listens on `addr`, the first client to connect is used as the peer.
+        pub fn listen<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+            let listener = TcpListener::bind(addr)?;
+            listener.set_nonblocking(true)?;
+
+            Ok(TcpSerial {
+                listener: Some(listener),
+                stream: None,
+
+                unsent: VecDeque::new(),
+            })
+        }
+
+        fn stream(&mut self) -> Option<&mut TcpStream> {
+            if self.stream.is_none() {
+                let accepted = self.listener.as_ref()
+                    .and_then(|listener| listener.accept().ok())
+                    .filter(|(stream, _)| stream.set_nonblocking(true).is_ok());
+
+                if let Some((stream, _)) = accepted {
+                    self.stream = Some(stream);
+                }
+            }
+
+            self.stream.as_mut()
+        }
+
+        /// Called when the peer disconnects or the connection fails, what it didn't take is lost.
+        fn disconnect(&mut self) {
+            self.stream = None;
+            self.unsent.clear();
+        }
+
+        /// Sends as many of the unsent bytes as the socket takes without blocking.
+        fn send_unsent(&mut self) {
+            // accepts a new client if there isn't one
+            self.stream();
+
+            while !self.unsent.is_empty() {
+                let stream = match &mut self.stream {
+                    Some(stream) => stream,
+                    None => return,
+                };
+
+                match stream.write(self.unsent.as_slices().0) {
+                    Ok(0) => return self.disconnect(),
+                    Ok(written) => drop(self.unsent.drain(..written)),
+
+                    Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => return,
+                    Err(_) => return self.disconnect(),
+                }
+            }
+        }
+    }
+
+    impl SerialBackend for TcpSerial {
+        fn read_byte(&mut self) -> Option<u8> {
+            self.send_unsent();
+
+            let mut buf = [0_u8];
+
+            match self.stream()?.read(&mut buf) {
+                Ok(0) => {
+                    // closed by the peer
+                    self.disconnect();
+                    None
+                }
+
+                Ok(_) => Some(buf[0]),
+
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::Interrupted) => None,
+
+                Err(_) => {
+                    self.disconnect();
+                    None
+                }
+            }
+        }
+
+        fn write_byte(&mut self, value: u8) {
+            if self.stream().is_none() {
+                return;
+            }
+
+            if self.unsent.len() == MAX_UNSENT {
+                self.unsent.pop_front();
+            }
+
+            self.unsent.push_back(value);
+            self.send_unsent();
+        }
+    }
+}
@@ -0,0 +1,310 @@
+use std::collections::VecDeque;
+
+use js_sys::Map;
+
+use super::super::{DeviceTrait, AddressableDeviceTrait, DeviceId, utils, CLOCK_HZ};
+
+// Register layout, 4 registers per channel:
+//
+//  +0 frequency lo (Hz)
+//  +1 frequency hi (Hz)
+//  +2 volume       (0-15, only the low nibble is used)
+//  +3 envelope     (see ENV_* bits, writing to it restarts the envelope)
+//
+// Channels 0 to 2 are square waves, channel 3 is noise. On the noise channel the frequency
+// is the rate at which the noise generator is shifted.
+
+const TONE_CHANNELS: usize = 3;
+const CHANNELS: usize = TONE_CHANNELS + 1;
+
+// keep in sync with DeviceId::fixed_size
+const REGISTERS_PER_CHANNEL: u16 = 4;
+
+const REG_FREQ_LO: u16 = 0;
+const REG_FREQ_HI: u16 = 1;
+const REG_VOLUME: u16 = 2;
+const REG_ENVELOPE: u16 = 3;
+
+/// Envelope step period, in 1/240s units (minus one).
+const ENV_RATE_MASK: u8 = 0x0F;
+const ENV_ENABLE: u8 = 1 << 4;
+/// If set, the envelope goes up instead of decaying.
+const ENV_ATTACK: u8 = 1 << 5;
+const ENV_LOOP: u8 = 1 << 6;
+
+const ENV_STEPS_PER_SECOND: u32 = 240;
+const MAX_LEVEL: u8 = 15;
+
+const DEFAULT_SAMPLE_RATE: u32 = 44_100;
+
+/// Max amount of samples kept while nobody is pulling them, the oldest ones are dropped.
+const BUFFER_LIMIT: usize = DEFAULT_SAMPLE_RATE as usize * 10;
+
+#[derive(Copy, Clone)]
+struct Channel {
+    freq: u16,
+    volume: u8,
+    envelope: u8,
+
+    env_level: u8,
+    env_counter: u32,
+
+    /// Position inside the current wave period, in the range 0..sample_rate
+    phase: u32,
+}
+
+impl Channel {
+    fn new() -> Self {
+        Channel {
+            freq: 0,
+            volume: 0,
+            envelope: 0,
+
+            env_level: 0,
+            env_counter: 0,
+
+            phase: 0,
+        }
+    }
+
+    fn restart_envelope(&mut self) {
+        self.env_counter = 0;
+
+        self.env_level = if (self.envelope & ENV_ATTACK) != 0 {
+            0
+        } else {
+            MAX_LEVEL
+        };
+    }
+
+    fn step_envelope(&mut self, sample_rate: u32) {
+        if (self.envelope & ENV_ENABLE) == 0 {
+            return;
+        }
+
+        let rate = ((self.envelope & ENV_RATE_MASK) as u32) + 1;
+        let step_len = ((rate as u64 * sample_rate as u64 / ENV_STEPS_PER_SECOND as u64) as u32).max(1);
+
+        self.env_counter += 1;
+
+        if self.env_counter >= step_len {
+            self.env_counter = 0;
+
+            let attack = (self.envelope & ENV_ATTACK) != 0;
+            let finished = if attack { self.env_level == MAX_LEVEL } else { self.env_level == 0 };
+
+            if !finished {
+                if attack {
+                    self.env_level += 1;
+                } else {
+                    self.env_level -= 1;
+                }
+            } else if (self.envelope & ENV_LOOP) != 0 {
+                self.restart_envelope();
+            }
+        }
+    }
+
+    /// Returns the current amplitude in the range 0.0..=1.0
+    fn amplitude(&self) -> f32 {
+        let volume = (self.volume & MAX_LEVEL) as f32 / MAX_LEVEL as f32;
+
+        if (self.envelope & ENV_ENABLE) != 0 {
+            volume * (self.env_level as f32 / MAX_LEVEL as f32)
+        } else {
+            volume
+        }
+    }
+}
+
+pub struct SoundGenerator {
+    channels: [Channel; CHANNELS],
+
+    /// 15 bit linear-feedback shift register used by the noise channel.
+    lfsr: u16,
+
+    sample_rate: u32,
+
+    /// Accumulates `sample_rate` every cycle, a sample is generated every time it reaches `CLOCK_HZ`.
+    sample_clock: u32,
+
+    samples: VecDeque<f32>,
+}
+
+impl SoundGenerator {
+    pub fn new() -> Self {
+        SoundGenerator {
+            channels: [Channel::new(); CHANNELS],
+
+            lfsr: 1,
+
+            sample_rate: DEFAULT_SAMPLE_RATE,
+
+            sample_clock: 0,
+
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Changes the output sample rate (at most one sample per cycle, `CLOCK_HZ`), buffered
+    /// samples are discarded.
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.sample_rate = sample_rate.clamp(1, CLOCK_HZ);
+
+        self.sample_clock = 0;
+        self.samples.clear();
+    }
+
+    /// Removes and returns up to `max` of the oldest buffered samples (mono, in the range -1.0..=1.0).
+    pub fn pull_samples(&mut self, max: usize) -> Vec<f32> {
+        let amount = max.min(self.samples.len());
+
+        self.samples.drain(..amount).collect()
+    }
+
+    /// Returns all the buffered samples as a 16-bit mono PCM WAV file, without removing them.
+    pub fn export_wav(&self) -> Vec<u8> {
+        const HEADER_SIZE: usize = 44;
+        const BYTES_PER_SAMPLE: u32 = 2;
+
+        let data_size = self.samples.len() as u32 * BYTES_PER_SAMPLE;
+        let mut wav = Vec::with_capacity(HEADER_SIZE + data_size as usize);
+
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_size).to_le_bytes());
+        wav.extend_from_slice(b"WAVE");
+
+        wav.extend_from_slice(b"fmt ");
+        wav.extend_from_slice(&16_u32.to_le_bytes()); // fmt chunk size
+        wav.extend_from_slice(&1_u16.to_le_bytes()); // PCM
+        wav.extend_from_slice(&1_u16.to_le_bytes()); // mono
+        wav.extend_from_slice(&self.sample_rate.to_le_bytes());
+        wav.extend_from_slice(&(self.sample_rate * BYTES_PER_SAMPLE).to_le_bytes()); // byte rate
+        wav.extend_from_slice(&(BYTES_PER_SAMPLE as u16).to_le_bytes()); // block align
+        wav.extend_from_slice(&16_u16.to_le_bytes()); // bits per sample
+
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_size.to_le_bytes());
+
+        for sample in &self.samples {
+            let pcm = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+
+            wav.extend_from_slice(&pcm.to_le_bytes());
+        }
+
+        wav
+    }
+
+    fn step_noise(&mut self) {
+        let feedback = (self.lfsr ^ (self.lfsr >> 1)) & 1;
+
+        self.lfsr = (self.lfsr >> 1) | (feedback << 14);
+    }
+
+    fn generate_sample(&mut self) {
+        let sample_rate = self.sample_rate;
+        let mut mixed = 0.0;
+
+        for channel in &mut self.channels[..TONE_CHANNELS] {
+            channel.step_envelope(sample_rate);
+
+            if channel.freq != 0 {
+                channel.phase = (channel.phase + channel.freq as u32) % sample_rate;
+
+                let high = channel.phase < sample_rate / 2;
+
+                mixed += if high { channel.amplitude() } else { -channel.amplitude() };
+            }
+        }
+
+        let noise = &mut self.channels[TONE_CHANNELS];
+        noise.step_envelope(sample_rate);
+
+        if noise.freq != 0 {
+            noise.phase += noise.freq as u32;
+
+            let shifts = noise.phase / sample_rate;
+            noise.phase %= sample_rate;
+
+            let amplitude = noise.amplitude();
+
+            for _ in 0..shifts {
+                self.step_noise();
+            }
+
+            mixed += if (self.lfsr & 1) != 0 { amplitude } else { -amplitude };
+        }
+
+        if self.samples.len() >= BUFFER_LIMIT {
+            self.samples.pop_front();
+        }
+
+        self.samples.push_back(mixed / CHANNELS as f32);
+    }
+}
+
+impl DeviceTrait for SoundGenerator {
+    fn tick(&mut self) {
+        self.sample_clock += self.sample_rate;
+
+        if self.sample_clock >= CLOCK_HZ {
+            self.sample_clock -= CLOCK_HZ;
+
+            self.generate_sample();
+        }
+    }
+
+    fn reset_system(&mut self) {
+        self.channels = [Channel::new(); CHANNELS];
+        self.lfsr = 1;
+
+        self.sample_clock = 0;
+        self.samples.clear();
+    }
+
+    fn reset_hard(&mut self) {
+        self.reset_system();
+    }
+
+    fn update_widget(&mut self, pkg: &Map) {
+        utils::js_map_add_entry_f64(pkg, "sampleRate", self.sample_rate);
+        utils::js_map_add_entry_f64(pkg, "buffered", self.samples.len() as f64);
+    }
+
+    fn device_id(&self) -> DeviceId {
+        DeviceId::SoundGenerator
+    }
+}
+
+impl AddressableDeviceTrait for SoundGenerator {
+    fn size(&self) -> u16 {
+        DeviceId::fixed_size(&DeviceId::SoundGenerator).unwrap()
+    }
+
+    fn read_unchecked(&self, offset: u16) -> u8 {
+        let channel = &self.channels[(offset / REGISTERS_PER_CHANNEL) as usize];
+
+        match offset % REGISTERS_PER_CHANNEL {
+            REG_FREQ_LO => channel.freq as u8,
+            REG_FREQ_HI => (channel.freq >> 8) as u8,
+            REG_VOLUME => channel.volume,
+            _ => channel.envelope,
+        }
+    }
+
+    fn write_unchecked(&mut self, offset: u16, value: u8) {
+        let channel = &mut self.channels[(offset / REGISTERS_PER_CHANNEL) as usize];
+
+        match offset % REGISTERS_PER_CHANNEL {
+            REG_FREQ_LO => channel.freq = (channel.freq & 0xFF00) | value as u16,
+            REG_FREQ_HI => channel.freq = (channel.freq & 0x00FF) | ((value as u16) << 8),
+            REG_VOLUME => channel.volume = value & MAX_LEVEL,
+            REG_ENVELOPE => {
+                channel.envelope = value;
+                channel.restart_envelope();
+            }
+
+            _ => {}
+        }
+    }
+}
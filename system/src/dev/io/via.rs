@@ -0,0 +1,684 @@
+use std::cell::Cell;
+
+use js_sys::Map;
+
+use super::super::{DeviceTrait, AddressableDeviceTrait, DeviceId, SignalLines, SignalLine, utils};
+
+// 6522 Versatile Interface Adapter.
+//
+// Register layout:
+//
+//  +0 ORB/IRB  +1 ORA/IRA  +2 DDRB     +3 DDRA
+//  +4 T1C-L    +5 T1C-H    +6 T1L-L    +7 T1L-H
+//  +8 T2C-L    +9 T2C-H    +A SR       +B ACR
+//  +C PCR      +D IFR      +E IER      +F ORA/IRA (no handshake)
+//
+// The pins are exposed with `DeviceTrait::port_output` and `DeviceTrait::set_port_input`, using the
+// `PORT_*` numbers. The control lines port uses the `CONTROL_*` bits.
+
+pub const PORT_A: u8 = 0;
+pub const PORT_B: u8 = 1;
+pub const PORT_CONTROL: u8 = 2;
+
+pub const CONTROL_CA1: u8 = 1 << 0;
+pub const CONTROL_CA2: u8 = 1 << 1;
+pub const CONTROL_CB1: u8 = 1 << 2;
+pub const CONTROL_CB2: u8 = 1 << 3;
+
+const REG_ORB: u16 = 0x0;
+const REG_ORA: u16 = 0x1;
+const REG_DDRB: u16 = 0x2;
+const REG_DDRA: u16 = 0x3;
+const REG_T1C_L: u16 = 0x4;
+const REG_T1C_H: u16 = 0x5;
+const REG_T1L_L: u16 = 0x6;
+const REG_T1L_H: u16 = 0x7;
+const REG_T2C_L: u16 = 0x8;
+const REG_T2C_H: u16 = 0x9;
+const REG_SR: u16 = 0xA;
+const REG_ACR: u16 = 0xB;
+const REG_PCR: u16 = 0xC;
+const REG_IFR: u16 = 0xD;
+const REG_IER: u16 = 0xE;
+const REG_ORA_NH: u16 = 0xF;
+
+// Interrupt flags
+const INT_CA2: u8 = 1 << 0;
+const INT_CA1: u8 = 1 << 1;
+const INT_SR: u8 = 1 << 2;
+const INT_CB2: u8 = 1 << 3;
+const INT_CB1: u8 = 1 << 4;
+const INT_T2: u8 = 1 << 5;
+const INT_T1: u8 = 1 << 6;
+const INT_ANY: u8 = 1 << 7;
+
+// Auxiliary control register
+const ACR_SR_MODE_SHIFT: u8 = 2;
+const ACR_SR_MODE_MASK: u8 = 0b111 << ACR_SR_MODE_SHIFT;
+const ACR_T2_PULSE_COUNT: u8 = 1 << 5;
+const ACR_T1_CONTINUOUS: u8 = 1 << 6;
+const ACR_T1_PB7: u8 = 1 << 7;
+
+// Shift register modes (ACR bits 2-4)
+const SR_DISABLED: u8 = 0b000;
+const SR_IN_T2: u8 = 0b001;
+const SR_IN_PHI2: u8 = 0b010;
+const SR_IN_CB1: u8 = 0b011;
+const SR_OUT_FREE_T2: u8 = 0b100;
+const SR_OUT_T2: u8 = 0b101;
+const SR_OUT_PHI2: u8 = 0b110;
+const SR_OUT_CB1: u8 = 0b111;
+
+// Peripheral control register, CA2/CB2 modes (3 bits each)
+const PCR_CA1_POSITIVE: u8 = 1 << 0;
+const PCR_CA2_SHIFT: u8 = 1;
+const PCR_CB1_POSITIVE: u8 = 1 << 4;
+const PCR_CB2_SHIFT: u8 = 5;
+
+// flags for the input modes
+const C2_INDEPENDENT: u8 = 0b001;
+const C2_POSITIVE: u8 = 0b010;
+const C2_OUTPUT: u8 = 0b100;
+
+// output modes
+const C2_HANDSHAKE: u8 = 0b100;
+const C2_PULSE: u8 = 0b101;
+const C2_LOW: u8 = 0b110;
+
+const PB6: u8 = 1 << 6;
+const PB7: u8 = 1 << 7;
+
+pub struct Via {
+    ora: u8,
+    orb: u8,
+    ddra: u8,
+    ddrb: u8,
+
+    /// Values driven into the pins from the outside.
+    input_a: u8,
+    input_b: u8,
+    input_control: u8,
+
+    t1_counter: u16,
+    t1_latch: u16,
+    t1_armed: bool,
+    t1_pb7: bool,
+
+    t2_counter: u16,
+    t2_latch_lo: u8,
+    t2_armed: bool,
+
+    sr: u8,
+    acr: u8,
+    pcr: u8,
+    ier: u8,
+
+    // reads only get a shared reference, but reading some registers clears flags or
+    // restarts the shift register/handshakes.
+    ifr: Cell<u8>,
+    sr_remaining: Cell<u8>,
+    ca2_out: Cell<bool>,
+    cb2_out: Cell<bool>,
+    /// Pulse handshake lines go back up on the next tick.
+    ca2_pulse: Cell<bool>,
+    cb2_pulse: Cell<bool>,
+
+    sr_phi2_toggle: bool,
+}
+
+impl Via {
+    pub fn new() -> Self {
+        let mut tmp = Via {
+            ora: 0,
+            orb: 0,
+            ddra: 0,
+            ddrb: 0,
+
+            input_a: 0xFF,
+            input_b: 0xFF,
+            input_control: 0xFF,
+
+            t1_counter: 0,
+            t1_latch: 0,
+            t1_armed: false,
+            t1_pb7: true,
+
+            t2_counter: 0,
+            t2_latch_lo: 0,
+            t2_armed: false,
+
+            sr: 0,
+            acr: 0,
+            pcr: 0,
+            ier: 0,
+
+            ifr: Cell::new(0),
+            sr_remaining: Cell::new(0),
+            ca2_out: Cell::new(true),
+            cb2_out: Cell::new(true),
+            ca2_pulse: Cell::new(false),
+            cb2_pulse: Cell::new(false),
+
+            sr_phi2_toggle: false,
+        };
+
+        tmp.reset_system();
+
+        tmp
+    }
+
+    /// Returns true while the device is requesting an interrupt.
+    pub fn irq_asserted(&self) -> bool {
+        (self.ifr.get() & self.ier & !INT_ANY) != 0
+    }
+
+    fn set_flags(&self, flags: u8) {
+        self.ifr.set(self.ifr.get() | flags);
+    }
+
+    fn clear_flags(&self, flags: u8) {
+        self.ifr.set(self.ifr.get() & !flags);
+    }
+
+    fn ifr_value(&self) -> u8 {
+        let ifr = self.ifr.get() & !INT_ANY;
+
+        if self.irq_asserted() { ifr | INT_ANY } else { ifr }
+    }
+
+    fn sr_mode(&self) -> u8 {
+        (self.acr & ACR_SR_MODE_MASK) >> ACR_SR_MODE_SHIFT
+    }
+
+    fn ca2_mode(&self) -> u8 {
+        (self.pcr >> PCR_CA2_SHIFT) & 0b111
+    }
+
+    fn cb2_mode(&self) -> u8 {
+        (self.pcr >> PCR_CB2_SHIFT) & 0b111
+    }
+
+    /// Port A read/write side effects, `handshake` is false for the "no handshake" register.
+    fn port_a_access(&self, handshake: bool) {
+        let mode = self.ca2_mode();
+
+        if (mode & (C2_OUTPUT | C2_INDEPENDENT)) == C2_INDEPENDENT {
+            self.clear_flags(INT_CA1);
+        } else {
+            self.clear_flags(INT_CA1 | INT_CA2);
+        }
+
+        if handshake {
+            match mode {
+                C2_HANDSHAKE => self.ca2_out.set(false),
+
+                C2_PULSE => {
+                    self.ca2_out.set(false);
+                    self.ca2_pulse.set(true);
+                }
+
+                _ => {}
+            }
+        }
+    }
+
+    fn port_b_access(&self) {
+        if (self.cb2_mode() & (C2_OUTPUT | C2_INDEPENDENT)) == C2_INDEPENDENT {
+            self.clear_flags(INT_CB1);
+        } else {
+            self.clear_flags(INT_CB1 | INT_CB2);
+        }
+    }
+
+    fn port_b_write_handshake(&self) {
+        match self.cb2_mode() {
+            C2_HANDSHAKE => self.cb2_out.set(false),
+
+            C2_PULSE => {
+                self.cb2_out.set(false);
+                self.cb2_pulse.set(true);
+            }
+
+            _ => {}
+        }
+    }
+
+    fn restart_shift_register(&self) {
+        self.clear_flags(INT_SR);
+        self.sr_remaining.set(8);
+    }
+
+    fn shift_once(&mut self) {
+        let mode = self.sr_mode();
+        let shifting_out = (mode & 0b100) != 0;
+        let free_running = mode == SR_OUT_FREE_T2;
+
+        if self.sr_remaining.get() == 0 && !free_running {
+            return;
+        }
+
+        if shifting_out {
+            let bit = self.sr >> 7;
+            self.sr = (self.sr << 1) | bit;
+
+            self.cb2_out.set(bit != 0);
+        } else {
+            let bit = u8::from((self.input_control & CONTROL_CB2) != 0);
+            self.sr = (self.sr << 1) | bit;
+        }
+
+        if !free_running {
+            self.sr_remaining.set(self.sr_remaining.get() - 1);
+
+            if self.sr_remaining.get() == 0 {
+                self.set_flags(INT_SR);
+            }
+        }
+    }
+
+    fn tick_t1(&mut self) {
+        let (counter, underflow) = self.t1_counter.overflowing_sub(1);
+        self.t1_counter = counter;
+
+        if underflow {
+            if (self.acr & ACR_T1_CONTINUOUS) != 0 {
+                self.set_flags(INT_T1);
+                self.t1_counter = self.t1_latch;
+                self.t1_pb7 = !self.t1_pb7;
+            } else if self.t1_armed {
+                self.set_flags(INT_T1);
+                self.t1_armed = false;
+                self.t1_pb7 = true;
+            }
+        }
+    }
+
+    fn tick_t2(&mut self) {
+        if (self.acr & ACR_T2_PULSE_COUNT) != 0 {
+            return; // decremented by PB6 falling edges instead
+        }
+
+        let (counter, underflow) = self.t2_counter.overflowing_sub(1);
+        self.t2_counter = counter;
+
+        // the shift register uses the low byte of T2 as its clock
+        let sr_mode = self.sr_mode();
+        let sr_uses_t2 = sr_mode == SR_IN_T2 || sr_mode == SR_OUT_T2 || sr_mode == SR_OUT_FREE_T2;
+
+        if sr_uses_t2 && (self.t2_counter & 0xFF) == 0xFF {
+            self.t2_counter = (self.t2_counter & 0xFF00) | self.t2_latch_lo as u16;
+            self.shift_once();
+        }
+
+        if underflow && self.t2_armed {
+            self.set_flags(INT_T2);
+            self.t2_armed = false;
+        }
+    }
+
+    fn decrement_t2_pulse(&mut self) {
+        let (counter, underflow) = self.t2_counter.overflowing_sub(1);
+        self.t2_counter = counter;
+
+        if underflow && self.t2_armed {
+            self.set_flags(INT_T2);
+            self.t2_armed = false;
+        }
+    }
+
+    fn set_control_input(&mut self, value: u8) {
+        let old = self.input_control;
+        self.input_control = value;
+
+        let rose = |bit: u8| (old & bit) == 0 && (value & bit) != 0;
+        let fell = |bit: u8| (old & bit) != 0 && (value & bit) == 0;
+
+        let ca1_positive = (self.pcr & PCR_CA1_POSITIVE) != 0;
+        if (ca1_positive && rose(CONTROL_CA1)) || (!ca1_positive && fell(CONTROL_CA1)) {
+            self.set_flags(INT_CA1);
+
+            if self.ca2_mode() == C2_HANDSHAKE {
+                self.ca2_out.set(true);
+            }
+        }
+
+        let cb1_positive = (self.pcr & PCR_CB1_POSITIVE) != 0;
+        if (cb1_positive && rose(CONTROL_CB1)) || (!cb1_positive && fell(CONTROL_CB1)) {
+            self.set_flags(INT_CB1);
+
+            if self.cb2_mode() == C2_HANDSHAKE {
+                self.cb2_out.set(true);
+            }
+        }
+
+        // the shift register external clock shifts on the CB1 rising edge
+        let sr_mode = self.sr_mode();
+        if (sr_mode == SR_IN_CB1 || sr_mode == SR_OUT_CB1) && rose(CONTROL_CB1) {
+            self.shift_once();
+        }
+
+        let ca2_mode = self.ca2_mode();
+        if (ca2_mode & C2_OUTPUT) == 0 {
+            let positive = (ca2_mode & C2_POSITIVE) != 0;
+
+            if (positive && rose(CONTROL_CA2)) || (!positive && fell(CONTROL_CA2)) {
+                self.set_flags(INT_CA2);
+            }
+        }
+
+        let cb2_mode = self.cb2_mode();
+        if (cb2_mode & C2_OUTPUT) == 0 && sr_mode == SR_DISABLED {
+            let positive = (cb2_mode & C2_POSITIVE) != 0;
+
+            if (positive && rose(CONTROL_CB2)) || (!positive && fell(CONTROL_CB2)) {
+                self.set_flags(INT_CB2);
+            }
+        }
+    }
+
+    fn port_a_pins(&self) -> u8 {
+        (self.ora & self.ddra) | (self.input_a & !self.ddra)
+    }
+
+    fn port_b_pins(&self) -> u8 {
+        let mut pins = (self.orb & self.ddrb) | (self.input_b & !self.ddrb);
+
+        if (self.acr & ACR_T1_PB7) != 0 {
+            pins = (pins & !PB7) | if self.t1_pb7 { PB7 } else { 0 };
+        }
+
+        pins
+    }
+
+    fn control_pins(&self) -> u8 {
+        let mut pins = self.input_control;
+
+        let ca2_mode = self.ca2_mode();
+        if (ca2_mode & C2_OUTPUT) != 0 {
+            let high = match ca2_mode {
+                C2_LOW => false,
+                C2_HANDSHAKE | C2_PULSE => self.ca2_out.get(),
+                _ => true,
+            };
+
+            pins = (pins & !CONTROL_CA2) | if high { CONTROL_CA2 } else { 0 };
+        }
+
+        let cb2_mode = self.cb2_mode();
+        let sr_out = (self.sr_mode() & 0b100) != 0;
+        if (cb2_mode & C2_OUTPUT) != 0 || sr_out {
+            let high = match cb2_mode {
+                _ if sr_out => self.cb2_out.get(),
+                C2_LOW => false,
+                C2_HANDSHAKE | C2_PULSE => self.cb2_out.get(),
+                _ => true,
+            };
+
+            pins = (pins & !CONTROL_CB2) | if high { CONTROL_CB2 } else { 0 };
+        }
+
+        pins
+    }
+}
+
+impl DeviceTrait for Via {
+    fn tick(&mut self) {
+        if self.ca2_pulse.replace(false) {
+            self.ca2_out.set(true);
+        }
+
+        if self.cb2_pulse.replace(false) {
+            self.cb2_out.set(true);
+        }
+
+        self.tick_t1();
+        self.tick_t2();
+
+        let sr_mode = self.sr_mode();
+        if sr_mode == SR_IN_PHI2 || sr_mode == SR_OUT_PHI2 {
+            // the shift clock runs at half the system clock
+            self.sr_phi2_toggle = !self.sr_phi2_toggle;
+
+            if self.sr_phi2_toggle {
+                self.shift_once();
+            }
+        }
+    }
+
+    fn reset_system(&mut self) {
+        // the timers, latches and shift register aren't cleared by a reset on the real chip,
+        // but clearing them makes the runs reproducible.
+        self.ora = 0;
+        self.orb = 0;
+        self.ddra = 0;
+        self.ddrb = 0;
+
+        self.t1_counter = 0xFFFF;
+        self.t1_latch = 0xFFFF;
+        self.t1_armed = false;
+        self.t1_pb7 = true;
+
+        self.t2_counter = 0xFFFF;
+        self.t2_latch_lo = 0xFF;
+        self.t2_armed = false;
+
+        self.sr = 0;
+        self.acr = 0;
+        self.pcr = 0;
+        self.ier = 0;
+
+        self.ifr.set(0);
+        self.sr_remaining.set(0);
+        self.ca2_out.set(true);
+        self.cb2_out.set(true);
+        self.ca2_pulse.set(false);
+        self.cb2_pulse.set(false);
+
+        self.sr_phi2_toggle = false;
+    }
+
+    fn reset_hard(&mut self) {
+        self.input_a = 0xFF;
+        self.input_b = 0xFF;
+        self.input_control = 0xFF;
+
+        self.reset_system();
+    }
+
+    fn update_widget(&mut self, pkg: &Map) {
+        utils::js_map_add_entry_f64(pkg, "portA", self.port_a_pins());
+        utils::js_map_add_entry_f64(pkg, "portB", self.port_b_pins());
+        utils::js_map_add_entry_f64(pkg, "ddrA", self.ddra);
+        utils::js_map_add_entry_f64(pkg, "ddrB", self.ddrb);
+        utils::js_map_add_entry_f64(pkg, "t1", self.t1_counter);
+        utils::js_map_add_entry_f64(pkg, "t2", self.t2_counter);
+        utils::js_map_add_entry_f64(pkg, "ifr", self.ifr_value());
+        utils::js_map_add_entry_f64(pkg, "ier", self.ier);
+    }
+
+    fn port_output(&self, port: u8) -> Option<u8> {
+        match port {
+            PORT_A => Some(self.port_a_pins()),
+            PORT_B => Some(self.port_b_pins()),
+            PORT_CONTROL => Some(self.control_pins()),
+
+            _ => None,
+        }
+    }
+
+    fn set_port_input(&mut self, port: u8, value: u8) {
+        match port {
+            PORT_A => self.input_a = value,
+
+            PORT_B => {
+                let pb6_fell = (self.input_b & PB6) != 0 && (value & PB6) == 0;
+                self.input_b = value;
+
+                if pb6_fell && (self.acr & ACR_T2_PULSE_COUNT) != 0 {
+                    self.decrement_t2_pulse();
+                }
+            }
+
+            PORT_CONTROL => self.set_control_input(value),
+
+            _ => {}
+        }
+    }
+
+    fn drive_signals(&self, signals: &mut SignalLines) {
+        if self.irq_asserted() {
+            signals.assert(SignalLine::Irq);
+        }
+    }
+
+    fn device_id(&self) -> DeviceId {
+        DeviceId::Via
+    }
+}
+
+impl AddressableDeviceTrait for Via {
+    fn size(&self) -> u16 {
+        DeviceId::fixed_size(&DeviceId::Via).unwrap()
+    }
+
+    fn read_unchecked(&self, offset: u16) -> u8 {
+        match offset {
+            REG_ORB => {
+                self.port_b_access();
+
+                // output pins read the output register, not the pin level
+                (self.orb & self.ddrb) | (self.port_b_pins() & !self.ddrb)
+            }
+
+            REG_ORA => {
+                self.port_a_access(true);
+
+                self.port_a_pins()
+            }
+
+            REG_DDRB => self.ddrb,
+            REG_DDRA => self.ddra,
+
+            REG_T1C_L => {
+                self.clear_flags(INT_T1);
+
+                self.t1_counter as u8
+            }
+
+            REG_T1C_H => (self.t1_counter >> 8) as u8,
+            REG_T1L_L => self.t1_latch as u8,
+            REG_T1L_H => (self.t1_latch >> 8) as u8,
+
+            REG_T2C_L => {
+                self.clear_flags(INT_T2);
+
+                self.t2_counter as u8
+            }
+
+            REG_T2C_H => (self.t2_counter >> 8) as u8,
+
+            REG_SR => {
+                self.restart_shift_register();
+
+                self.sr
+            }
+
+            REG_ACR => self.acr,
+            REG_PCR => self.pcr,
+            REG_IFR => self.ifr_value(),
+            REG_IER => self.ier | INT_ANY,
+
+            _ => {
+                self.port_a_access(false);
+
+                self.port_a_pins()
+            }
+        }
+    }
+
+    fn peek_unchecked(&self, offset: u16) -> u8 {
+        match offset {
+            REG_ORB => (self.orb & self.ddrb) | (self.port_b_pins() & !self.ddrb),
+            REG_ORA | REG_ORA_NH => self.port_a_pins(),
+
+            REG_T1C_L => self.t1_counter as u8,
+            REG_T2C_L => self.t2_counter as u8,
+            REG_SR => self.sr,
+
+            _ => self.read_unchecked(offset),
+        }
+    }
+
+    fn write_unchecked(&mut self, offset: u16, value: u8) {
+        match offset {
+            REG_ORB => {
+                self.orb = value;
+
+                self.port_b_access();
+                self.port_b_write_handshake();
+            }
+
+            REG_ORA => {
+                self.ora = value;
+
+                self.port_a_access(true);
+            }
+
+            REG_DDRB => self.ddrb = value,
+            REG_DDRA => self.ddra = value,
+
+            REG_T1C_L | REG_T1L_L => self.t1_latch = (self.t1_latch & 0xFF00) | value as u16,
+
+            REG_T1C_H => {
+                self.t1_latch = (self.t1_latch & 0x00FF) | ((value as u16) << 8);
+                self.t1_counter = self.t1_latch;
+                self.t1_armed = true;
+                self.t1_pb7 = false;
+
+                self.clear_flags(INT_T1);
+            }
+
+            REG_T1L_H => {
+                self.t1_latch = (self.t1_latch & 0x00FF) | ((value as u16) << 8);
+
+                self.clear_flags(INT_T1);
+            }
+
+            REG_T2C_L => self.t2_latch_lo = value,
+
+            REG_T2C_H => {
+                self.t2_counter = ((value as u16) << 8) | self.t2_latch_lo as u16;
+                self.t2_armed = true;
+
+                self.clear_flags(INT_T2);
+            }
+
+            REG_SR => {
+                self.sr = value;
+
+                self.restart_shift_register();
+            }
+
+            REG_ACR => self.acr = value,
+            REG_PCR => self.pcr = value,
+
+            REG_IFR => self.clear_flags(value & !INT_ANY),
+
+            REG_IER => {
+                if (value & INT_ANY) != 0 {
+                    self.ier |= value & !INT_ANY;
+                } else {
+                    self.ier &= !value;
+                }
+            }
+
+            REG_ORA_NH => {
+                self.ora = value;
+
+                self.port_a_access(false);
+            }
+
+            _ => {}
+        }
+    }
+}
@@ -8,7 +8,7 @@ extern {
     pub type DeviceRepresentation;
 
     #[wasm_bindgen(static_method_of = DeviceRepresentationFactory)]
-    pub fn new(id: DeviceId, start: u16, end: u16, uid: u16) -> DeviceRepresentation;
+    pub fn new(id: DeviceId, start: u32, end: u32, uid: u16) -> DeviceRepresentation;
 }
 
 impl DeviceRepresentationFactory {
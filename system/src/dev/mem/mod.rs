@@ -2,4 +2,4 @@ mod rom;
 mod ram;
 
 pub use rom::Rom;
-pub use ram::Ram;
\ No newline at end of file
+pub use ram::{Ram, RamInit, RamInitKind};
\ No newline at end of file
@@ -1,11 +1,49 @@
-use js_sys::Map;
+use wasm_bindgen::prelude::*;
 
-use super::super::{DeviceTrait, AddressableDeviceTrait, DeviceId, utils};
+use super::super::{DeviceTrait, AddressableDeviceTrait, DeviceId, DirtyPages};
+
+/// Kinds of `RamInit`, for the JS api.
+#[wasm_bindgen]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RamInitKind {
+    Zero = 0,
+    Pattern = 1,
+    Random = 2,
+}
+
+/// What the RAM contains after a reset, real RAM powers on with garbage instead of zeros.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub enum RamInit {
+    #[default]
+    Zero,
+
+    /// Every byte set to the value.
+    Pattern(u8),
+
+    /// Pseudo random bytes from the seed, the same ones on every reset.
+    Random(u32),
+}
+
+impl RamInit {
+    /// `value` is the byte for `RamInitKind::Pattern` and the seed for `RamInitKind::Random`.
+    pub fn from_kind(kind: RamInitKind, value: u32) -> Self {
+        match kind {
+            RamInitKind::Zero => RamInit::Zero,
+            RamInitKind::Pattern => RamInit::Pattern(value as u8),
+            RamInitKind::Random => RamInit::Random(value),
+        }
+    }
+}
 
 pub struct Ram {
     contents: Box<[u8]>,
 
-    widget_update: bool,
+    dirty_pages: DirtyPages,
+
+    init: RamInit,
+
+    /// One bit per byte, set once it's written after a reset.
+    initialized: Box<[u64]>,
 }
 
 impl Ram {
@@ -13,30 +51,74 @@ impl Ram {
         Ram {
             contents: vec![0_u8; size as usize].into_boxed_slice(),
 
-            widget_update: true,
+            dirty_pages: DirtyPages::with_size(size),
+
+            init: RamInit::Zero,
+
+            initialized: vec![0_u64; (size as usize).div_ceil(64)].into_boxed_slice(),
         }
     }
+
+    /// Sets the contents of the next resets.
+    pub fn set_init(&mut self, init: RamInit) {
+        self.init = init;
+    }
+
+    /// Takes the contents of `other` (as many bytes as fit) with the bytes written since the
+    /// last reset, the rest get the contents of a reset. Used when resizing the device.
+    pub fn copy_contents_from(&mut self, other: &Ram) {
+        self.fill();
+
+        let len = self.contents.len().min(other.contents.len());
+        self.contents[..len].copy_from_slice(&other.contents[..len]);
+
+        for offset in 0..len as u16 {
+            if other.is_initialized(offset) {
+                self.mark_initialized(offset);
+            }
+        }
+
+        self.dirty_pages.mark_all();
+    }
+
+    fn fill(&mut self) {
+        match self.init {
+            RamInit::Zero => self.contents.fill(0),
+            RamInit::Pattern(value) => self.contents.fill(value),
+
+            RamInit::Random(seed) => {
+                // xorshift32, that gets stuck on 0
+                let mut x = if seed == 0 { 0x2545_F491 } else { seed };
+
+                for val in &mut *self.contents {
+                    x ^= x << 13;
+                    x ^= x >> 17;
+                    x ^= x << 5;
+
+                    *val = (x >> 24) as u8;
+                }
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn mark_initialized(&mut self, offset: u16) {
+        self.initialized[offset as usize / 64] |= 1 << (offset % 64);
+    }
 }
 
 impl DeviceTrait for Ram {
     fn reset_system(&mut self) {
-        for val in &mut *self.contents {
-            *val = 0;
-        }
+        self.fill();
+        self.initialized.fill(0);
 
-        self.widget_update = true;
+        self.dirty_pages.mark_all();
     }
 
     fn reset_hard(&mut self) {
         self.reset_system();
     }
 
-    fn update_widget(&mut self, pkg: &Map) {
-        utils::js_map_add_entry_bool(pkg, "update", self.widget_update);
-
-        self.widget_update = false;
-    }
-
     fn device_id(&self) -> DeviceId {
         DeviceId::Ram
     }
@@ -54,16 +136,28 @@ impl AddressableDeviceTrait for Ram {
     }
 
     fn write_unchecked(&mut self, offset: u16, value: u8) {
-        self.widget_update = true;
+        self.dirty_pages.mark(offset);
+        self.mark_initialized(offset);
 
         unsafe {
             *self.contents.get_unchecked_mut(offset as usize) = value;
         }
     }
 
-    fn data_ptr(&mut self) -> *const u8 {
-        self.widget_update = true;
+    fn poke_unchecked(&mut self, offset: u16, value: u8) -> bool {
+        self.dirty_pages.mark(offset);
+        self.mark_initialized(offset);
+
+        self.contents[offset as usize] = value;
+
+        true
+    }
+
+    fn is_initialized(&self, offset: u16) -> bool {
+        self.initialized[offset as usize / 64] & (1 << (offset % 64)) != 0
+    }
 
-        self.contents.as_ptr()
+    fn take_dirty_pages(&mut self) -> Vec<u16> {
+        self.dirty_pages.take()
     }
 }
@@ -1,11 +1,9 @@
-use js_sys::Map;
-
-use super::super::{DeviceTrait, AddressableDeviceTrait, DeviceId, utils};
+use super::super::{DeviceTrait, AddressableDeviceTrait, DeviceId, DirtyPages};
 
 pub struct Rom {
     contents: Box<[u8]>,
 
-    widget_update: bool,
+    dirty_pages: DirtyPages,
 }
 
 impl Rom {
@@ -13,7 +11,7 @@ impl Rom {
         Rom {
             contents: vec![0_u8; size as usize].into_boxed_slice(),
 
-            widget_update: true,
+            dirty_pages: DirtyPages::with_size(size),
         }
     }
 }
@@ -28,13 +26,7 @@ impl DeviceTrait for Rom {
             *val = 0;
         }
 
-        self.widget_update = true;
-    }
-
-    fn update_widget(&mut self, pkg: &Map) {
-        utils::js_map_add_entry_bool(pkg, "update", self.widget_update);
-
-        self.widget_update = false;
+        self.dirty_pages.mark_all();
     }
 
     fn device_id(&self) -> DeviceId {
@@ -53,9 +45,15 @@ impl AddressableDeviceTrait for Rom {
         }
     }
 
-    fn data_ptr(&mut self) -> *const u8 {
-        self.widget_update = true;
+    fn poke_unchecked(&mut self, offset: u16, value: u8) -> bool {
+        self.dirty_pages.mark(offset);
+
+        self.contents[offset as usize] = value;
+
+        true
+    }
 
-        self.contents.as_ptr()
+    fn take_dirty_pages(&mut self) -> Vec<u16> {
+        self.dirty_pages.take()
     }
 }
@@ -4,19 +4,28 @@ pub mod mem;
 pub mod io;
 
 mod holder;
+mod dirty_pages;
 
 mod id;
 mod js_representation;
 mod factory;
+mod registry;
+mod signals;
 
 pub mod utils;
 
 pub use traits::{DeviceTrait, AddressableDeviceTrait};
 
 pub use holder::DeviceHolder;
+pub use dirty_pages::{DirtyPages, PAGE_SIZE};
 
 pub use id::DeviceId;
 pub use js_representation::{DeviceRepresentation, DeviceRepresentationFactory};
 pub use factory::DeviceFactory;
+pub use signals::{SignalLines, SignalLine};
+pub use registry::{DeviceRegistry, DeviceType, DeviceSize, DeviceConstructor};
 
 pub type BoxedDev = Box<dyn AddressableDeviceTrait>;
+
+/// There is no real clock in the system, this is only used by devices to convert ticks into time.
+pub const CLOCK_HZ: u32 = 1_000_000;
@@ -0,0 +1,181 @@
+use super::{BoxedDev, DeviceId, DeviceFactory};
+use crate::system::SystemError;
+
+/// Creates a device of the given size, the size has already been validated by the registry.
+pub type DeviceConstructor = Box<dyn Fn(u16) -> BoxedDev>;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum DeviceSize {
+    /// The device always uses this amount of addresses, it's created with a size of 0.
+    Fixed(u16),
+
+    /// The size is chosen by the user, and can't be 0.
+    Variable,
+}
+
+pub struct DeviceType {
+    name: String,
+    id: u8,
+    size: DeviceSize,
+
+    constructor: DeviceConstructor,
+}
+
+impl DeviceType {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    pub fn size(&self) -> DeviceSize {
+        self.size
+    }
+}
+
+/// Devices registered by `DeviceRegistry::new`, their ids are reserved.
+const BUILTINS: [DeviceId; 16] = [
+    DeviceId::PixelScreen,
+    DeviceId::CharLcd,
+    DeviceId::ColorScreen,
+    DeviceId::AsciiIOBuffer,
+    DeviceId::Acia,
+    DeviceId::KeyboardMatrix,
+    DeviceId::Joystick,
+    DeviceId::KeyLatch,
+    DeviceId::Semihost,
+    DeviceId::SoundGenerator,
+    DeviceId::Prng,
+    DeviceId::RealTimeClock,
+    DeviceId::Via,
+    DeviceId::Pia,
+    DeviceId::Rom,
+    DeviceId::Ram,
+];
+
+fn is_reserved_id(id: u8) -> bool {
+    [DeviceId::Custom, DeviceId::CPU].iter().chain(BUILTINS.iter())
+        .any(|reserved| *reserved as u8 == id)
+}
+
+/// Device types that can be instantiated by name, this allows adding devices without touching
+/// `DeviceId` and `DeviceFactory`.
+///
+/// Devices created from a custom type should return `DeviceId::Custom` as their `device_id`,
+/// and the name they were registered with as their `type_name`.
+pub struct DeviceRegistry {
+    types: Vec<DeviceType>,
+}
+
+impl DeviceRegistry {
+    /// Creates a registry with all the built in devices, using the same names as `DeviceId`.
+    ///
+    /// The ids of the built in devices are their `DeviceId`, so custom devices must use other ids.
+    pub fn new() -> Self {
+        let mut tmp = Self::empty();
+
+        for id in BUILTINS.iter().copied() {
+            let size = match id.fixed_size() {
+                Some(size) => DeviceSize::Fixed(size),
+                None => DeviceSize::Variable,
+            };
+
+            let constructor = move |size| {
+                let factory_size = if id.fixed_size().is_some() { 0 } else { size };
+
+                DeviceFactory::with_size(id, factory_size)
+                    .expect("the registry has already validated the size")
+            };
+
+            tmp.push(id.name(), id as u8, size, Box::new(constructor));
+        }
+
+        tmp
+    }
+
+    /// Creates a registry without any device type.
+    pub fn empty() -> Self {
+        DeviceRegistry {
+            types: Vec::new(),
+        }
+    }
+
+    /// Adds a new device type.
+    ///
+    /// # Returns
+    /// Returns false (and does nothing) if the name or the id are already in use, if the id belongs
+    /// to a `DeviceId`, or if the size is `DeviceSize::Fixed(0)`.
+    pub fn register(&mut self, name: &str, id: u8, size: DeviceSize, constructor: DeviceConstructor) -> bool {
+        let is_taken = self.by_name(name).is_some() || self.by_id(id).is_some() || is_reserved_id(id);
+
+        if is_taken || size == DeviceSize::Fixed(0) {
+            false
+        } else {
+            self.push(name, id, size, constructor);
+
+            true
+        }
+    }
+
+    fn push(&mut self, name: &str, id: u8, size: DeviceSize, constructor: DeviceConstructor) {
+        self.types.push(DeviceType {
+            name: name.to_string(),
+            id,
+            size,
+
+            constructor,
+        });
+    }
+
+    pub fn by_name(&self, name: &str) -> Option<&DeviceType> {
+        self.types.iter().find(|dev_type| dev_type.name == name)
+    }
+
+    pub fn by_id(&self, id: u8) -> Option<&DeviceType> {
+        self.types.iter().find(|dev_type| dev_type.id == id)
+    }
+
+    pub fn types(&self) -> &[DeviceType] {
+        &self.types
+    }
+
+    /// Creates a new device of type `name` with size `size`, following the same rules as `DeviceFactory`:
+    /// if the device has a fixed size, `size` must be a 0.
+    ///
+    /// # Returns
+    /// Returns `Err(SystemError::UnknownDeviceType)` if there is no such type, and
+    /// `Err(SystemError::InvalidSize)` if the size is invalid.
+    pub fn create(&self, name: &str, size: u16) -> Result<BoxedDev, SystemError> {
+        let dev_type = self.by_name(name)
+            .ok_or_else(|| SystemError::UnknownDeviceType(name.to_string()))?;
+
+        let invalid_size = || SystemError::InvalidSize {
+            type_name: name.to_string(),
+            size,
+        };
+
+        let actual_size = match (dev_type.size, size) {
+            (DeviceSize::Fixed(fixed), 0) => fixed,
+            (DeviceSize::Variable, size) if size != 0 => size,
+
+            _ => return Err(invalid_size()),
+        };
+
+        let dev = (dev_type.constructor)(actual_size);
+
+        // a misbehaving constructor would break the address mapping
+        if dev.size() == actual_size {
+            Ok(dev)
+        } else {
+            Err(invalid_size())
+        }
+    }
+}
+
+impl Default for DeviceRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
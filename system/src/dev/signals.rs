@@ -0,0 +1,93 @@
+use wasm_bindgen::prelude::*;
+
+/// Control lines shared by the CPU and the devices.
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Copy, Clone)]
+pub enum SignalLine {
+    /// Maskable interrupt, level triggered and wired-OR: it stays asserted while any device asserts it.
+    Irq = 0,
+
+    /// Non maskable interrupt, only the transition from released to asserted triggers an interrupt.
+    Nmi = 1,
+
+    /// Asserting it pulls RDY low, pausing the CPU until it's released.
+    Rdy = 2,
+
+    /// Keeps the CPU in reset while asserted.
+    Reset = 3,
+}
+
+const LINES: usize = 4;
+
+/// Levels of the `SignalLine`s.
+///
+/// Devices assert the lines every cycle (see `DeviceTrait::drive_signals`), so a line is released as
+/// soon as no device drives it. The host can also hold lines, those stay as set until changed.
+pub struct SignalLines {
+    asserted: [bool; LINES],
+    held_by_host: [bool; LINES],
+
+    nmi_was_asserted: bool,
+    nmi_pending: bool,
+}
+
+impl SignalLines {
+    pub fn new() -> Self {
+        SignalLines {
+            asserted: [false; LINES],
+            held_by_host: [false; LINES],
+
+            nmi_was_asserted: false,
+            nmi_pending: false,
+        }
+    }
+
+    pub fn assert(&mut self, line: SignalLine) {
+        self.asserted[line as usize] = true;
+    }
+
+    pub fn is_asserted(&self, line: SignalLine) -> bool {
+        self.asserted[line as usize]
+    }
+
+    pub fn set_held_by_host(&mut self, line: SignalLine, asserted: bool) {
+        self.held_by_host[line as usize] = asserted;
+        self.asserted[line as usize] |= asserted;
+    }
+
+    /// Returns true (once) if NMI went from released to asserted.
+    pub fn take_nmi(&mut self) -> bool {
+        std::mem::replace(&mut self.nmi_pending, false)
+    }
+
+    /// Releases the lines not held by the host, before the devices drive them again.
+    pub fn begin_cycle(&mut self) {
+        self.asserted = self.held_by_host;
+    }
+
+    /// Latches the NMI edge, once every device had the chance to drive the lines.
+    pub fn end_cycle(&mut self) {
+        let nmi = self.is_asserted(SignalLine::Nmi);
+
+        if nmi && !self.nmi_was_asserted {
+            self.nmi_pending = true;
+        }
+
+        self.nmi_was_asserted = nmi;
+    }
+
+    /// Forgets everything but the lines held by the host.
+    pub fn reset(&mut self) {
+        self.asserted = self.held_by_host;
+
+        self.nmi_was_asserted = false;
+        self.nmi_pending = false;
+    }
+}
+
+impl Default for SignalLines {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,4 +1,5 @@
 use super::DeviceTrait;
+use super::super::dirty_pages;
 
 pub trait AddressableDeviceTrait: DeviceTrait {
     /// Returns the actual size of the device in the address bus.
@@ -9,6 +10,14 @@ pub trait AddressableDeviceTrait: DeviceTrait {
     /// The offset has already been validated by `system::MemManager`.
     fn read_unchecked(&self, offset: u16) -> u8;
 
+    /// Read value at `offset` from outside the system (debuggers, memory views...), without side effects.
+    ///
+    /// By default it's the same as `Self::read_unchecked`, devices whose reads change their state
+    /// (clearing flags, popping buffers...) must override it.
+    fn peek_unchecked(&self, offset: u16) -> u8 {
+        self.read_unchecked(offset)
+    }
+
     /// Write `value` at `offset`.
     ///
     /// The offset has already been validated by `system::MemManager`.
@@ -16,9 +25,31 @@ pub trait AddressableDeviceTrait: DeviceTrait {
     fn write_unchecked(&mut self, offset: u16, value: u8) {
     }
 
-    /// # Returns
-    /// Returns a raw ptr to the device data if possible or a null ptr.
-    fn data_ptr(&mut self) -> *const u8 {
-        std::ptr::null()
+    /// Write `value` at `offset` from outside the system (loaders, debuggers...), without side effects.
+    ///
+    /// Unlike `Self::write_unchecked`, it should also work on read only memory.
+    /// Returns false if the value wasn't written, by default it does nothing, as most devices
+    /// don't have any memory to load.
+    #[allow(unused_variables)]
+    fn poke_unchecked(&mut self, offset: u16, value: u8) -> bool {
+        false
+    }
+
+    /// Returns false if the byte at `offset` wasn't written since the last reset, so it still has
+    /// whatever the memory powered on with (see `System::set_uninit_reads_enabled`).
+    ///
+    /// By default it's always true, only RAM keeps track of it.
+    #[allow(unused_variables)]
+    fn is_initialized(&self, offset: u16) -> bool {
+        true
+    }
+
+    /// Returns the pages (of `dev::PAGE_SIZE` bytes) that might have changed since the last call,
+    /// in increasing order, so memory views only need to copy those with `Self::peek_unchecked`.
+    ///
+    /// By default every page is always reported, devices with memory should track the changes
+    /// with `dev::DirtyPages`.
+    fn take_dirty_pages(&mut self) -> Vec<u16> {
+        (0..dirty_pages::page_count(self.size()) as u16).collect()
     }
 }
@@ -1,8 +1,11 @@
+use std::any::Any;
+
 use js_sys::Map;
 
-use super::super::DeviceId;
+use super::super::{DeviceId, SignalLines};
 
-pub trait DeviceTrait {
+// `Any` allows downcasting a device to its concrete type, see `system::MemManager::device_as_mut`.
+pub trait DeviceTrait: Any {
     /// This method is called on every system + cpu tick.
     fn tick(&mut self) {}
 
@@ -27,5 +30,36 @@ pub trait DeviceTrait {
     fn update_widget(&mut self, _pkg: &Map) {
     }
 
+    /// Returns the value of the pins of `port`, for devices that have IO ports.
+    ///
+    /// The port numbers are defined by each device.
+    fn port_output(&self, _port: u8) -> Option<u8> {
+        None
+    }
+
+    /// Drives the pins of `port` from the outside, for devices that have IO ports.
+    ///
+    /// Pins configured as outputs by the device should ignore the value.
+    fn set_port_input(&mut self, _port: u8, _value: u8) {
+    }
+
+    /// Asserts the signal lines driven by the device, called on every tick after `Self::tick`.
+    ///
+    /// The lines are released at the start of every tick, so they must be asserted on every call.
+    fn drive_signals(&self, _signals: &mut SignalLines) {
+    }
+
+    /// Returns the exit code the program asked for through the device, if it did since the last call.
+    ///
+    /// It's checked after every tick and every write to the device, and stops `System::run`.
+    fn take_exit_code(&mut self) -> Option<u8> {
+        None
+    }
+
     fn device_id(&self) -> DeviceId;
+
+    /// Name of the device type in the `DeviceRegistry`, custom devices must override it.
+    fn type_name(&self) -> &str {
+        self.device_id().name()
+    }
 }
@@ -10,3 +10,17 @@ pub fn js_map_add_entry_f64<T: Into<f64>>(pkg: &Map, key: &str, value: T) {
 pub fn js_map_add_entry_bool(pkg: &Map, key: &str, value: bool) {
     pkg.set(&JsValue::from_str(key), &JsValue::from_bool(value));
 }
+
+/// Milliseconds elapsed since the unix epoch, according to the host.
+#[cfg(target_arch = "wasm32")]
+pub fn host_time_millis() -> u64 {
+    js_sys::Date::now() as u64
+}
+
+/// Milliseconds elapsed since the unix epoch, according to the host.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn host_time_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |time| time.as_millis() as u64)
+}
@@ -3,3 +3,26 @@ mod utils;
 mod system;
 mod cpu;
 mod dev;
+
+pub use system::{System, PortWire};
+pub use system::{SystemError, SystemErrorKind};
+pub use system::{HaltCondition, StopReason, RunResult};
+pub use system::{CallFrame, FrameKind, StackMismatch, MismatchKind};
+pub use system::{Profiler, SubroutineProfile};
+pub use system::AccessKind;
+pub use system::WriteRecord;
+pub use system::UninitRead;
+pub use system::{MachineConfig, CpuVariant, StartPc, DeviceConfig, DeviceContents, ContentsSource, ConfigError};
+pub use system::MachineProfile;
+pub use system::{Image, ImageSegment, ImageFormat, ImageError};
+
+pub use cpu::{CpuRegister, CpuFlag};
+
+pub use dev::{DeviceTrait, AddressableDeviceTrait, BoxedDev, DeviceId};
+pub use dev::{SignalLines, SignalLine};
+pub use dev::mem::{RamInit, RamInitKind};
+pub use dev::{DeviceRegistry, DeviceType, DeviceSize, DeviceConstructor};
+
+pub use dev::io::{SerialBackend, BufferSerial};
+#[cfg(not(target_arch = "wasm32"))]
+pub use dev::io::{StdioSerial, FileSerial, TcpSerial};
@@ -0,0 +1,79 @@
+use wasm_bindgen::prelude::*;
+
+use super::system::ADDRESS_SPACE_SIZE;
+
+#[wasm_bindgen]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum AccessKind {
+    Read = 0,
+    Write = 1,
+
+    /// Opcode fetches, they don't count as reads.
+    Execute = 2,
+}
+
+/// Counts the reads, writes and executions (opcode fetches) of every address, for heatmaps and
+/// code coverage. The counts saturate instead of wrapping around.
+pub struct AccessCounters {
+    reads: Vec<u32>,
+    writes: Vec<u32>,
+    executes: Vec<u32>,
+}
+
+impl Default for AccessCounters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AccessCounters {
+    pub fn new() -> Self {
+        AccessCounters {
+            reads: vec![0; ADDRESS_SPACE_SIZE as usize],
+            writes: vec![0; ADDRESS_SPACE_SIZE as usize],
+            executes: vec![0; ADDRESS_SPACE_SIZE as usize],
+        }
+    }
+
+    fn counts(&self, kind: AccessKind) -> &[u32] {
+        match kind {
+            AccessKind::Read => &self.reads,
+            AccessKind::Write => &self.writes,
+            AccessKind::Execute => &self.executes,
+        }
+    }
+
+    #[inline(always)]
+    pub fn count(&mut self, kind: AccessKind, addr: u16) {
+        let counts = match kind {
+            AccessKind::Read => &mut self.reads,
+            AccessKind::Write => &mut self.writes,
+            AccessKind::Execute => &mut self.executes,
+        };
+
+        let count = &mut counts[addr as usize];
+        *count = count.saturating_add(1);
+    }
+
+    pub fn count_at(&self, kind: AccessKind, addr: u16) -> u32 {
+        self.counts(kind)[addr as usize]
+    }
+
+    /// The counts of `len` addresses from `start`, stopping at the end of the address space.
+    pub fn range(&self, kind: AccessKind, start: u16, len: u32) -> Vec<u32> {
+        let start = start as usize;
+        let end = (start + len as usize).min(ADDRESS_SPACE_SIZE as usize);
+
+        self.counts(kind)[start..end].to_vec()
+    }
+
+    /// Like `Self::range`, with every kind of access added together.
+    pub fn total_range(&self, start: u16, len: u32) -> Vec<u32> {
+        let start = start as usize;
+        let end = (start + len as usize).min(ADDRESS_SPACE_SIZE as usize);
+
+        (start..end)
+            .map(|addr| self.reads[addr].saturating_add(self.writes[addr]).saturating_add(self.executes[addr]))
+            .collect()
+    }
+}
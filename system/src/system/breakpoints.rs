@@ -0,0 +1,230 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write;
+
+use crate::cpu::{CPU, CpuRegister};
+
+use super::MemManager;
+use super::expr::Expr;
+
+/// Log lines kept at most until taken, the oldest are dropped.
+const MAX_LOG_LINES: usize = 1000;
+
+/// How a value is written in a log message.
+#[derive(Copy, Clone, PartialEq)]
+enum Radix {
+    Decimal,
+    Hex,
+    Binary,
+}
+
+enum LogPart {
+    Text(String),
+    Value(Expr, Radix),
+}
+
+/// A log message, text with `{expr}` placeholders (`{expr:x}` for hex, `{expr:b}` for binary)
+/// replaced by the value of the expression. `{{` and `}}` are a literal brace.
+struct LogMessage {
+    parts: Vec<LogPart>,
+}
+
+impl LogMessage {
+    /// Parses `text`, the error is the text of the bad expression (or the whole message) and a
+    /// description of the problem.
+    fn parse(text: &str) -> Result<Self, (String, String)> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut rest = text;
+
+        while let Some(c) = rest.chars().next() {
+            if rest.starts_with("{{") || rest.starts_with("}}") {
+                literal.push(c);
+                rest = &rest[2..];
+            } else if c == '{' {
+                let end = rest.find('}')
+                    .ok_or_else(|| (text.to_string(), "unclosed '{'".to_string()))?;
+
+                let placeholder = &rest[1..end];
+
+                let (source, radix) = match placeholder.rsplit_once(':') {
+                    Some((source, "x")) | Some((source, "X")) => (source, Radix::Hex),
+                    Some((source, "b")) | Some((source, "B")) => (source, Radix::Binary),
+                    _ => (placeholder, Radix::Decimal),
+                };
+
+                let expr = Expr::parse(source).map_err(|reason| (source.to_string(), reason))?;
+
+                if !literal.is_empty() {
+                    parts.push(LogPart::Text(std::mem::take(&mut literal)));
+                }
+
+                parts.push(LogPart::Value(expr, radix));
+                rest = &rest[end + 1..];
+            } else {
+                literal.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+
+        if !literal.is_empty() {
+            parts.push(LogPart::Text(literal));
+        }
+
+        Ok(LogMessage { parts })
+    }
+
+    fn format(&self, cpu: &CPU, mem: &MemManager) -> String {
+        let mut text = String::new();
+
+        for part in &self.parts {
+            match part {
+                LogPart::Text(literal) => text += literal,
+
+                LogPart::Value(expr, radix) => {
+                    let value = expr.eval(cpu, mem);
+
+                    match radix {
+                        Radix::Decimal => write!(text, "{}", value).unwrap(),
+                        Radix::Hex => write!(text, "${:X}", value).unwrap(),
+                        Radix::Binary => write!(text, "%{:b}", value).unwrap(),
+                    }
+                }
+            }
+        }
+
+        text
+    }
+}
+
+struct Breakpoint {
+    /// Hits only count if it's true (not 0).
+    condition: Option<Expr>,
+
+    /// The hit it starts firing at, 0 and 1 fire from the first one.
+    hit_count: u32,
+
+    hits: u32,
+
+    /// Makes it a logpoint, it writes the message instead of stopping.
+    log_message: Option<LogMessage>,
+}
+
+// Breakpoints checked by `System::run` (and the other ways of running) before every instruction.
+// A breakpoint is hit when the pc gets to its address and its condition is true, and fires from
+// its `hit_count`th hit on, stopping the run or (for logpoints) writing to the log.
+
+pub struct Breakpoints {
+    breakpoints: BTreeMap<u16, Breakpoint>,
+
+    log: VecDeque<String>,
+}
+
+impl Default for Breakpoints {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Breakpoints {
+    pub fn new() -> Self {
+        Breakpoints {
+            breakpoints: BTreeMap::new(),
+            log: VecDeque::new(),
+        }
+    }
+
+    /// Sets (or replaces) the breakpoint at `addr`, `condition` and `log_message` are ignored if
+    /// they are empty.
+    ///
+    /// The error is the text of the bad expression and a description of the problem.
+    pub fn set(&mut self, addr: u16, condition: &str, hit_count: u32, log_message: &str) -> Result<(), (String, String)> {
+        let condition = match condition.trim() {
+            "" => None,
+            condition => Some(Expr::parse(condition).map_err(|reason| (condition.to_string(), reason))?),
+        };
+
+        let log_message = match log_message {
+            "" => None,
+            log_message => Some(LogMessage::parse(log_message)?),
+        };
+
+        self.breakpoints.insert(addr, Breakpoint {
+            condition,
+            hit_count,
+            hits: 0,
+            log_message,
+        });
+
+        Ok(())
+    }
+
+    /// Returns true if there was a breakpoint at `addr`.
+    pub fn remove(&mut self, addr: u16) -> bool {
+        self.breakpoints.remove(&addr).is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.breakpoints.clear();
+        self.log.clear();
+    }
+
+    pub fn reset_hits(&mut self) {
+        for breakpoint in self.breakpoints.values_mut() {
+            breakpoint.hits = 0;
+        }
+    }
+
+    /// The addresses with a breakpoint, in order.
+    pub fn addresses(&self) -> Vec<u16> {
+        self.breakpoints.keys().copied().collect()
+    }
+
+    pub fn hits(&self, addr: u16) -> Option<u32> {
+        self.breakpoints.get(&addr).map(|breakpoint| breakpoint.hits)
+    }
+
+    pub fn take_log(&mut self) -> Vec<String> {
+        self.log.drain(..).collect()
+    }
+
+    /// Called before executing the instruction at the pc, returns true if the run has to stop.
+    #[inline(always)]
+    pub fn check(&mut self, cpu: &CPU, mem: &MemManager) -> bool {
+        if self.breakpoints.is_empty() {
+            return false;
+        }
+
+        let pc = cpu.register(CpuRegister::Pc);
+
+        let breakpoint = match self.breakpoints.get_mut(&pc) {
+            Some(breakpoint) => breakpoint,
+            None => return false,
+        };
+
+        if breakpoint.condition.as_ref().is_some_and(|condition| condition.eval(cpu, mem) == 0) {
+            return false;
+        }
+
+        breakpoint.hits = breakpoint.hits.saturating_add(1);
+
+        if breakpoint.hits < breakpoint.hit_count {
+            return false;
+        }
+
+        match &breakpoint.log_message {
+            Some(log_message) => {
+                let line = format!("${:04X}: {}", pc, log_message.format(cpu, mem));
+
+                if self.log.len() == MAX_LOG_LINES {
+                    self.log.pop_front();
+                }
+
+                self.log.push_back(line);
+
+                false
+            }
+
+            None => true,
+        }
+    }
+}
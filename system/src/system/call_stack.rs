@@ -0,0 +1,234 @@
+use wasm_bindgen::prelude::*;
+
+use crate::cpu::OperationStart;
+
+// Shadow call stack, built by watching the calls and returns the cpu executes, so it doesn't
+// depend on what the program leaves in the hardware stack.
+
+const OPCODE_BRK: u8 = 0x00;
+const OPCODE_JSR: u8 = 0x20;
+const OPCODE_RTI: u8 = 0x40;
+const OPCODE_RTS: u8 = 0x60;
+
+/// Bytes between a `JSR` and the address it returns to.
+const JSR_SIZE: u16 = 3;
+
+/// A `BRK` returns 2 bytes after itself, skipping its padding byte.
+const BRK_SIZE: u16 = 2;
+
+/// Frames kept at most, the oldest are dropped (a 256 bytes stack can't hold more calls).
+const MAX_FRAMES: usize = 256;
+
+/// Mismatches kept at most until taken, the oldest are dropped.
+const MAX_MISMATCHES: usize = 64;
+
+#[wasm_bindgen]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum FrameKind {
+    Jsr = 0,
+    Brk = 1,
+    Irq = 2,
+    Nmi = 3,
+}
+
+#[wasm_bindgen]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct CallFrame {
+    pub kind: FrameKind,
+
+    /// Address of the `JSR` or `BRK`, or of the instruction interrupted by an IRQ or NMI.
+    pub caller_pc: u16,
+
+    /// Address of the subroutine or interrupt handler.
+    pub target: u16,
+
+    /// The S register before the call, the same it should have after returning.
+    pub sp: u8,
+}
+
+impl CallFrame {
+    /// Where the matching return should continue.
+    pub fn return_addr(&self) -> u16 {
+        match self.kind {
+            FrameKind::Jsr => self.caller_pc.wrapping_add(JSR_SIZE),
+            FrameKind::Brk => self.caller_pc.wrapping_add(BRK_SIZE),
+            FrameKind::Irq | FrameKind::Nmi => self.caller_pc,
+        }
+    }
+}
+
+/// How an operation changed the frames, see `CallStack::operation_done`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum StackChange {
+    None,
+
+    /// A frame was added, even if the oldest one had to be dropped for it.
+    Call,
+
+    /// Frames were removed by a return.
+    Return,
+}
+
+#[wasm_bindgen]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum MismatchKind {
+    /// The return didn't continue after the call, the return address in the stack was changed.
+    /// `expected` and `actual` are the addresses.
+    ReturnAddress = 0,
+
+    /// The return also discarded the frames of calls that never returned (the stack pointer was
+    /// moved past them). `expected` and `actual` are the S register values.
+    UnwoundFrames = 1,
+
+    /// A return without a call to return from, like an `RTS` used as an indirect jump.
+    /// `expected` is 0 and `actual` is the address the return continued at.
+    NoFrame = 2,
+
+    /// A `RTS` from an interrupt or a `RTI` from a subroutine. `expected` and `actual` are the
+    /// addresses.
+    WrongReturn = 3,
+}
+
+#[wasm_bindgen]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct StackMismatch {
+    pub kind: MismatchKind,
+
+    /// Address of the `RTS` or `RTI`.
+    pub pc: u16,
+
+    pub expected: u16,
+    pub actual: u16,
+}
+
+pub struct CallStack {
+    frames: Vec<CallFrame>,
+
+    mismatches: Vec<StackMismatch>,
+
+    /// The call or return being executed, with the pc and S register it started with.
+    pending: Option<(OperationStart, u16, u8)>,
+}
+
+impl Default for CallStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CallStack {
+    pub fn new() -> Self {
+        CallStack {
+            frames: Vec::new(),
+            mismatches: Vec::new(),
+
+            pending: None,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.mismatches.clear();
+
+        self.pending = None;
+    }
+
+    /// The frames, from the outermost to the innermost.
+    pub fn frames(&self) -> &[CallFrame] {
+        &self.frames
+    }
+
+    pub fn take_mismatches(&mut self) -> Vec<StackMismatch> {
+        std::mem::take(&mut self.mismatches)
+    }
+
+    /// Called when the cpu starts an operation at `pc`, with the S register it starts with.
+    pub fn operation_started(&mut self, start: OperationStart, pc: u16, s: u8) {
+        let is_tracked = match start {
+            OperationStart::Opcode(opcode) => matches!(opcode, OPCODE_BRK | OPCODE_JSR | OPCODE_RTI | OPCODE_RTS),
+            OperationStart::Irq | OperationStart::Nmi => true,
+        };
+
+        self.pending = if is_tracked { Some((start, pc, s)) } else { None };
+    }
+
+    /// Called when the cpu finishes an operation, with the resulting pc and S register.
+    pub fn operation_done(&mut self, pc: u16, s: u8) -> StackChange {
+        let (start, start_pc, start_s) = match self.pending.take() {
+            Some(pending) => pending,
+            None => return StackChange::None,
+        };
+
+        let call_kind = match start {
+            OperationStart::Opcode(OPCODE_JSR) => FrameKind::Jsr,
+            OperationStart::Opcode(OPCODE_BRK) => FrameKind::Brk,
+            OperationStart::Irq => FrameKind::Irq,
+            OperationStart::Nmi => FrameKind::Nmi,
+
+            OperationStart::Opcode(opcode) => {
+                return if self.returned(opcode == OPCODE_RTI, start_pc, pc, s) {
+                    StackChange::Return
+                } else {
+                    StackChange::None
+                };
+            }
+        };
+
+        if self.frames.len() == MAX_FRAMES {
+            self.frames.remove(0);
+        }
+
+        self.frames.push(CallFrame {
+            kind: call_kind,
+            caller_pc: start_pc,
+            target: pc,
+            sp: start_s,
+        });
+
+        StackChange::Call
+    }
+
+    /// Returns true if the frames changed.
+    fn returned(&mut self, is_rti: bool, return_pc: u16, pc: u16, s: u8) -> bool {
+        // a clean return leaves S where it was before the call, the frames it went past are
+        // calls that will never return. A S below the frame means a return address pushed by
+        // the program, not one from a call
+        let unwound = self.frames.iter().rev()
+            .take_while(|frame| frame.sp < s)
+            .count();
+
+        if unwound > 0 {
+            let innermost = self.frames[self.frames.len() - 1];
+            self.mismatch(MismatchKind::UnwoundFrames, return_pc, innermost.sp as u16, s as u16);
+
+            self.frames.truncate(self.frames.len() - unwound);
+        }
+
+        let frame = match self.frames.last() {
+            Some(frame) if frame.sp == s => *frame,
+
+            _ => {
+                self.mismatch(MismatchKind::NoFrame, return_pc, 0, pc);
+                return unwound > 0;
+            }
+        };
+
+        self.frames.pop();
+
+        if is_rti == (frame.kind == FrameKind::Jsr) {
+            self.mismatch(MismatchKind::WrongReturn, return_pc, frame.return_addr(), pc);
+        } else if frame.return_addr() != pc {
+            self.mismatch(MismatchKind::ReturnAddress, return_pc, frame.return_addr(), pc);
+        }
+
+        true
+    }
+
+    fn mismatch(&mut self, kind: MismatchKind, pc: u16, expected: u16, actual: u16) {
+        if self.mismatches.len() == MAX_MISMATCHES {
+            self.mismatches.remove(0);
+        }
+
+        self.mismatches.push(StackMismatch { kind, pc, expected, actual });
+    }
+}
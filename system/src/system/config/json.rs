@@ -0,0 +1,346 @@
+use std::fmt;
+
+// Minimal JSON support for the machine configuration files, we don't want to pull serde
+// and its dependencies just for this.
+
+#[derive(Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+
+    /// The order of the members is kept, so exported files are stable.
+    Object(Vec<(String, Json)>),
+}
+
+pub struct JsonError {
+    pub line: usize,
+    pub column: usize,
+
+    pub message: String,
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl Json {
+    pub fn parse(text: &str) -> Result<Json, JsonError> {
+        let mut parser = Parser {
+            chars: text.chars().collect(),
+            pos: 0,
+        };
+
+        let value = parser.value()?;
+
+        parser.skip_whitespace();
+
+        if parser.pos < parser.chars.len() {
+            Err(parser.error("unexpected data after the end of the document"))
+        } else {
+            Ok(value)
+        }
+    }
+
+    /// Returns the member `key`, if `self` is an object and has it.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members.iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+
+            _ => None,
+        }
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn error(&self, message: &str) -> JsonError {
+        let consumed = &self.chars[..self.pos.min(self.chars.len())];
+
+        let line = consumed.iter().filter(|c| **c == '\n').count() + 1;
+        let column = consumed.iter().rev().take_while(|c| **c != '\n').count() + 1;
+
+        JsonError {
+            line,
+            column,
+
+            message: message.to_string(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ') | Some('\t') | Some('\n') | Some('\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), JsonError> {
+        self.skip_whitespace();
+
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", expected)))
+        }
+    }
+
+    fn keyword(&mut self, word: &str, value: Json) -> Result<Json, JsonError> {
+        let end = self.pos + word.len();
+
+        if end <= self.chars.len() && self.chars[self.pos..end].iter().copied().eq(word.chars()) {
+            self.pos = end;
+            Ok(value)
+        } else {
+            Err(self.error("invalid value"))
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, JsonError> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some('"') => self.string().map(Json::String),
+
+            Some('t') => self.keyword("true", Json::Bool(true)),
+            Some('f') => self.keyword("false", Json::Bool(false)),
+            Some('n') => self.keyword("null", Json::Null),
+
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+
+            Some(_) => Err(self.error("invalid value")),
+            None => Err(self.error("unexpected end of the document")),
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, JsonError> {
+        self.expect('{')?;
+
+        let mut members = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(Json::Object(members));
+        }
+
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.error("expected a member name"));
+            }
+
+            let name = self.string()?;
+
+            if members.iter().any(|(other, _)| *other == name) {
+                return Err(self.error(&format!("duplicated member \"{}\"", name)));
+            }
+
+            self.expect(':')?;
+
+            let value = self.value()?;
+            members.push((name, value));
+
+            self.skip_whitespace();
+
+            match self.peek() {
+                Some(',') => self.pos += 1,
+
+                Some('}') => {
+                    self.pos += 1;
+                    return Ok(Json::Object(members));
+                }
+
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, JsonError> {
+        self.expect('[')?;
+
+        let mut items = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Ok(Json::Array(items));
+        }
+
+        loop {
+            items.push(self.value()?);
+
+            self.skip_whitespace();
+
+            match self.peek() {
+                Some(',') => self.pos += 1,
+
+                Some(']') => {
+                    self.pos += 1;
+                    return Ok(Json::Array(items));
+                }
+
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.expect('"')?;
+
+        let mut result = String::new();
+
+        loop {
+            let c = self.peek().ok_or_else(|| self.error("unterminated string"))?;
+            self.pos += 1;
+
+            match c {
+                '"' => return Ok(result),
+
+                '\\' => {
+                    let escaped = self.peek().ok_or_else(|| self.error("unterminated string"))?;
+                    self.pos += 1;
+
+                    let unescaped = match escaped {
+                        '"' => '"',
+                        '\\' => '\\',
+                        '/' => '/',
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+
+                        'u' => {
+                            let end = self.pos + 4;
+
+                            let code = self.chars.get(self.pos..end)
+                                .filter(|digits| digits.iter().all(|c| c.is_ascii_hexdigit()))
+                                .map(|digits| digits.iter().collect::<String>())
+                                .and_then(|digits| u32::from_str_radix(&digits, 16).ok())
+                                .ok_or_else(|| self.error("invalid unicode escape"))?;
+
+                            self.pos = end;
+
+                            // surrogate pairs are not needed for configuration files
+                            std::char::from_u32(code).unwrap_or('\u{FFFD}')
+                        }
+
+                        _ => return Err(self.error("invalid escape sequence")),
+                    };
+
+                    result.push(unescaped);
+                }
+
+                c if (c as u32) < 0x20 => return Err(self.error("control character in string")),
+
+                c => result.push(c),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, JsonError> {
+        let start = self.pos;
+
+        while let Some(c) = self.peek() {
+            if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' || c == 'e' || c == 'E' {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+
+        let text: String = self.chars[start..self.pos].iter().collect();
+
+        text.parse::<f64>()
+            .map(Json::Number)
+            .map_err(|_| {
+                self.pos = start;
+                self.error("invalid number")
+            })
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, value: &str) -> fmt::Result {
+    write!(f, "\"")?;
+
+    for c in value.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+
+            c => write!(f, "{}", c)?,
+        }
+    }
+
+    write!(f, "\"")
+}
+
+impl Json {
+    fn write_indented(&self, f: &mut fmt::Formatter, indent: usize) -> fmt::Result {
+        const INDENT: &str = "    ";
+
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(value) => write!(f, "{}", value),
+            Json::Number(value) => write!(f, "{}", value),
+            Json::String(value) => write_string(f, value),
+
+            Json::Array(items) if items.is_empty() => write!(f, "[]"),
+            Json::Object(members) if members.is_empty() => write!(f, "{{}}"),
+
+            Json::Array(items) => {
+                writeln!(f, "[")?;
+
+                for (i, item) in items.iter().enumerate() {
+                    write!(f, "{}", INDENT.repeat(indent + 1))?;
+                    item.write_indented(f, indent + 1)?;
+                    writeln!(f, "{}", if i + 1 < items.len() { "," } else { "" })?;
+                }
+
+                write!(f, "{}]", INDENT.repeat(indent))
+            }
+
+            Json::Object(members) => {
+                writeln!(f, "{{")?;
+
+                for (i, (name, value)) in members.iter().enumerate() {
+                    write!(f, "{}", INDENT.repeat(indent + 1))?;
+                    write_string(f, name)?;
+                    write!(f, ": ")?;
+                    value.write_indented(f, indent + 1)?;
+                    writeln!(f, "{}", if i + 1 < members.len() { "," } else { "" })?;
+                }
+
+                write!(f, "{}}}", INDENT.repeat(indent))
+            }
+        }
+    }
+}
+
+/// Pretty prints the value, with 4 spaces of indentation.
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_indented(f, 0)
+    }
+}
@@ -0,0 +1,360 @@
+use std::fmt;
+
+use super::json::Json;
+use super::super::PortWire;
+
+// Machine description, as JSON:
+//
+// {
+//     "cpu": {
+//         "variant": "6502",
+//         "startPc": "0x0600"
+//     },
+//
+//     "devices": [
+//         {
+//             "type": "Rom",
+//             "uid": 1,
+//             "start": "0x8000",
+//             "size": "0x1000",
+//             "mirror": "0x8000",
+//             "contents": { "file": "program.bin", "offset": "0x0000" }
+//         }
+//     ],
+//
+//     "wires": [
+//         { "from": 2, "fromPort": 0, "to": 3, "toPort": 1 }
+//     ]
+// }
+//
+// - "startPc" is an address or "reset", to start from the reset vector ($FFFC).
+// - "type" is the name of the device in the `DeviceRegistry`.
+// - "size" must be missing (or 0) for fixed size devices.
+// - "mirror" (optional) is the size of the address range used by the device, which is repeated to fill it.
+// - "contents" (optional) are loaded into the device, either from a "file" or from "hex" (a string of hex digits).
+// - "wires" (optional) connect device ports, see `System::connect_ports`.
+//
+// Numbers can be written as JSON numbers, or as strings in hex ("0x8000" or "$8000").
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum CpuVariant {
+    /// NMOS 6502, without decimal mode.
+    Nmos6502,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum StartPc {
+    Address(u16),
+    ResetVector,
+}
+
+#[derive(Clone, PartialEq)]
+pub enum ContentsSource {
+    /// Path of the file, relative to the configuration file.
+    File(String),
+
+    Data(Vec<u8>),
+}
+
+#[derive(Clone, PartialEq)]
+pub struct DeviceContents {
+    pub source: ContentsSource,
+
+    /// Offset inside the device where the contents start.
+    pub offset: u16,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct DeviceConfig {
+    pub type_name: String,
+    pub uid: u16,
+
+    pub start: u16,
+    /// 0 for fixed size devices.
+    pub size: u16,
+
+    /// Size of the address range, if bigger than the device.
+    pub mirror: Option<u32>,
+
+    pub contents: Option<DeviceContents>,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct MachineConfig {
+    pub cpu_variant: CpuVariant,
+    pub start_pc: StartPc,
+
+    pub devices: Vec<DeviceConfig>,
+    pub wires: Vec<PortWire>,
+}
+
+#[derive(Debug)]
+pub struct ConfigError {
+    message: String,
+}
+
+impl ConfigError {
+    pub fn new(message: String) -> Self {
+        ConfigError {
+            message,
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+fn error<T>(path: &str, message: &str) -> Result<T, ConfigError> {
+    Err(ConfigError::new(format!("{}: {}", path, message)))
+}
+
+fn parse_number(path: &str, value: &Json, max: u32) -> Result<u32, ConfigError> {
+    let number = match value {
+        Json::Number(n) if n.fract() == 0.0 && *n >= 0.0 && *n <= max as f64 => Some(*n as u32),
+
+        Json::String(text) => {
+            let parsed = if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix('$')) {
+                u32::from_str_radix(hex, 16)
+            } else {
+                text.parse::<u32>()
+            };
+
+            parsed.ok().filter(|n| *n <= max)
+        }
+
+        _ => None,
+    };
+
+    number.map_or_else(
+        || error(path, &format!("expected a number between 0 and {:#x}", max)),
+        Ok,
+    )
+}
+
+fn member<'a>(path: &str, object: &'a Json, name: &str) -> Result<&'a Json, ConfigError> {
+    object.get(name)
+        .map_or_else(|| error(path, &format!("missing \"{}\"", name)), Ok)
+}
+
+fn member_number(path: &str, object: &Json, name: &str, max: u32) -> Result<u32, ConfigError> {
+    let value = member(path, object, name)?;
+
+    parse_number(&format!("{}.{}", path, name), value, max)
+}
+
+fn optional_number(path: &str, object: &Json, name: &str, max: u32) -> Result<Option<u32>, ConfigError> {
+    match object.get(name) {
+        None | Some(Json::Null) => Ok(None),
+        Some(value) => parse_number(&format!("{}.{}", path, name), value, max).map(Some),
+    }
+}
+
+fn member_string<'a>(path: &str, object: &'a Json, name: &str) -> Result<&'a str, ConfigError> {
+    match member(path, object, name)? {
+        Json::String(text) => Ok(text),
+        _ => error(&format!("{}.{}", path, name), "expected a string"),
+    }
+}
+
+fn parse_hex_data(path: &str, text: &str) -> Result<Vec<u8>, ConfigError> {
+    let digits: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+
+    if !digits.len().is_multiple_of(2) {
+        return error(path, "odd number of hex digits");
+    }
+
+    digits.chunks(2)
+        .map(|pair| {
+            let pair: String = pair.iter().collect();
+
+            u8::from_str_radix(&pair, 16)
+                .map_or_else(|_| error(path, &format!("invalid hex byte \"{}\"", pair)), Ok)
+        })
+        .collect()
+}
+
+fn hex(value: u32) -> Json {
+    Json::String(format!("0x{:04X}", value))
+}
+
+impl DeviceConfig {
+    fn from_json(path: &str, value: &Json) -> Result<Self, ConfigError> {
+        if !matches!(value, Json::Object(_)) {
+            return error(path, "expected an object");
+        }
+
+        let contents = match value.get("contents") {
+            None | Some(Json::Null) => None,
+
+            Some(contents) => {
+                let contents_path = format!("{}.contents", path);
+
+                let source = match (contents.get("file"), contents.get("hex")) {
+                    (Some(Json::String(file)), None) => ContentsSource::File(file.clone()),
+
+                    (None, Some(Json::String(data))) => {
+                        ContentsSource::Data(parse_hex_data(&format!("{}.hex", contents_path), data)?)
+                    }
+
+                    _ => return error(&contents_path, "expected either a \"file\" or a \"hex\" string"),
+                };
+
+                let offset = optional_number(&contents_path, contents, "offset", 0xFFFF)?
+                    .unwrap_or(0) as u16;
+
+                Some(DeviceContents {
+                    source,
+                    offset,
+                })
+            }
+        };
+
+        Ok(DeviceConfig {
+            type_name: member_string(path, value, "type")?.to_string(),
+            uid: member_number(path, value, "uid", 0xFFFF)? as u16,
+
+            start: member_number(path, value, "start", 0xFFFF)? as u16,
+            size: optional_number(path, value, "size", 0xFFFF)?.unwrap_or(0) as u16,
+
+            mirror: optional_number(path, value, "mirror", 0x1_0000)?,
+
+            contents,
+        })
+    }
+
+    fn to_json(&self) -> Json {
+        let mut members = vec![
+            ("type".to_string(), Json::String(self.type_name.clone())),
+            ("uid".to_string(), Json::Number(self.uid as f64)),
+            ("start".to_string(), hex(self.start as u32)),
+        ];
+
+        if self.size != 0 {
+            members.push(("size".to_string(), hex(self.size as u32)));
+        }
+
+        if let Some(mirror) = self.mirror {
+            members.push(("mirror".to_string(), hex(mirror)));
+        }
+
+        if let Some(contents) = &self.contents {
+            let source = match &contents.source {
+                ContentsSource::File(file) => ("file".to_string(), Json::String(file.clone())),
+
+                ContentsSource::Data(data) => {
+                    let digits = data.iter().map(|byte| format!("{:02X}", byte)).collect();
+
+                    ("hex".to_string(), Json::String(digits))
+                }
+            };
+
+            members.push(("contents".to_string(), Json::Object(vec![
+                source,
+                ("offset".to_string(), hex(contents.offset as u32)),
+            ])));
+        }
+
+        Json::Object(members)
+    }
+}
+
+fn wire_from_json(path: &str, value: &Json) -> Result<PortWire, ConfigError> {
+    Ok(PortWire {
+        from_uid: member_number(path, value, "from", 0xFFFF)? as u16,
+        from_port: member_number(path, value, "fromPort", 0xFF)? as u8,
+
+        to_uid: member_number(path, value, "to", 0xFFFF)? as u16,
+        to_port: member_number(path, value, "toPort", 0xFF)? as u8,
+    })
+}
+
+fn wire_to_json(wire: &PortWire) -> Json {
+    Json::Object(vec![
+        ("from".to_string(), Json::Number(wire.from_uid as f64)),
+        ("fromPort".to_string(), Json::Number(wire.from_port as f64)),
+        ("to".to_string(), Json::Number(wire.to_uid as f64)),
+        ("toPort".to_string(), Json::Number(wire.to_port as f64)),
+    ])
+}
+
+impl MachineConfig {
+    pub fn from_json(text: &str) -> Result<Self, ConfigError> {
+        let root = Json::parse(text)
+            .map_err(|e| ConfigError::new(format!("invalid JSON, {}", e)))?;
+
+        if !matches!(root, Json::Object(_)) {
+            return error("machine", "expected an object");
+        }
+
+        let cpu = member("machine", &root, "cpu")?;
+
+        let cpu_variant = match member_string("cpu", cpu, "variant")? {
+            "6502" => CpuVariant::Nmos6502,
+            other => return error("cpu.variant", &format!("unsupported variant \"{}\"", other)),
+        };
+
+        let start_pc = match member("cpu", cpu, "startPc")? {
+            Json::String(text) if text == "reset" => StartPc::ResetVector,
+            value => StartPc::Address(parse_number("cpu.startPc", value, 0xFFFF)? as u16),
+        };
+
+        let devices = match member("machine", &root, "devices")? {
+            Json::Array(items) => items.iter()
+                .enumerate()
+                .map(|(i, item)| DeviceConfig::from_json(&format!("devices[{}]", i), item))
+                .collect::<Result<Vec<_>, _>>()?,
+
+            _ => return error("devices", "expected an array"),
+        };
+
+        let wires = match root.get("wires") {
+            None | Some(Json::Null) => Vec::new(),
+
+            Some(Json::Array(items)) => items.iter()
+                .enumerate()
+                .map(|(i, item)| wire_from_json(&format!("wires[{}]", i), item))
+                .collect::<Result<Vec<_>, _>>()?,
+
+            Some(_) => return error("wires", "expected an array"),
+        };
+
+        Ok(MachineConfig {
+            cpu_variant,
+            start_pc,
+
+            devices,
+            wires,
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        let variant = match self.cpu_variant {
+            CpuVariant::Nmos6502 => "6502",
+        };
+
+        let start_pc = match self.start_pc {
+            StartPc::Address(addr) => hex(addr as u32),
+            StartPc::ResetVector => Json::String("reset".to_string()),
+        };
+
+        let root = Json::Object(vec![
+            ("cpu".to_string(), Json::Object(vec![
+                ("variant".to_string(), Json::String(variant.to_string())),
+                ("startPc".to_string(), start_pc),
+            ])),
+
+            ("devices".to_string(), Json::Array(
+                self.devices.iter().map(DeviceConfig::to_json).collect()
+            )),
+
+            ("wires".to_string(), Json::Array(
+                self.wires.iter().map(wire_to_json).collect()
+            )),
+        ]);
+
+        root.to_string()
+    }
+}
@@ -0,0 +1,6 @@
+mod json;
+mod machine;
+mod profiles;
+
+pub use machine::{MachineConfig, CpuVariant, StartPc, DeviceConfig, DeviceContents, ContentsSource, ConfigError};
+pub use profiles::MachineProfile;
@@ -0,0 +1,125 @@
+use super::machine::{MachineConfig, CpuVariant, StartPc, DeviceConfig};
+use super::super::PortWire;
+use crate::dev::DeviceId;
+use crate::dev::io::{via, char_lcd};
+
+/// Ready made machines, see `System::load_profile`.
+#[derive(Copy, Clone, PartialEq)]
+pub enum MachineProfile {
+    /// Same layout as the easy6502 tutorial: a 32x32 screen at $0200, a random byte at $FE,
+    /// the last key pressed at $FF and RAM everywhere else. Programs start at $0600.
+    Easy6502,
+
+    /// Ben Eater's breadboard computer: 16K of RAM at $0000, a VIA at $6000 and a 32K ROM at $8000,
+    /// with a 16x2 LCD wired to the VIA (data on port B, E/RW/RS on bits 7-5 of port A).
+    ///
+    /// The LCD is also mapped at $4000, as devices need an address, but it's meant to be driven
+    /// through the VIA. The program starts from the reset vector.
+    BenEater,
+
+    /// Apple-1: 4K of RAM at $0000, the keyboard/display PIA at $D010 (mirrored all over $Dxxx)
+    /// and a 256 bytes ROM at $FF00 for the monitor. The program starts from the reset vector.
+    Apple1,
+}
+
+impl MachineProfile {
+    pub const ALL: [MachineProfile; 3] = [
+        MachineProfile::Easy6502,
+        MachineProfile::BenEater,
+        MachineProfile::Apple1,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MachineProfile::Easy6502 => "easy6502",
+            MachineProfile::BenEater => "ben-eater",
+            MachineProfile::Apple1 => "apple-1",
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<MachineProfile> {
+        Self::ALL.iter().copied().find(|profile| profile.name() == name)
+    }
+
+    pub fn config(&self) -> MachineConfig {
+        match self {
+            MachineProfile::Easy6502 => MachineConfig {
+                cpu_variant: CpuVariant::Nmos6502,
+                start_pc: StartPc::Address(0x0600),
+
+                devices: vec![
+                    device(DeviceId::Ram, 1, 0x0000, 0xFE),
+                    device(DeviceId::Prng, 2, 0x00FE, 0),
+                    device(DeviceId::KeyLatch, 3, 0x00FF, 0),
+                    device(DeviceId::Ram, 4, 0x0100, 0x100),
+                    device(DeviceId::ColorScreen, 5, 0x0200, 0),
+                    device(DeviceId::Ram, 6, 0x0600, 0xFA00),
+                ],
+
+                wires: Vec::new(),
+            },
+
+            MachineProfile::BenEater => MachineConfig {
+                cpu_variant: CpuVariant::Nmos6502,
+                start_pc: StartPc::ResetVector,
+
+                devices: vec![
+                    device(DeviceId::Ram, 1, 0x0000, 0x4000),
+                    device(DeviceId::CharLcd, 2, 0x4000, 0),
+                    // the VIA is selected by A13-A14 only, so it repeats until $7FFF
+                    DeviceConfig {
+                        mirror: Some(0x2000),
+                        ..device(DeviceId::Via, 3, 0x6000, 0)
+                    },
+                    device(DeviceId::Rom, 4, 0x8000, 0x8000),
+                ],
+
+                wires: vec![
+                    wire(3, via::PORT_B, 2, char_lcd::PORT_DATA),
+                    wire(3, via::PORT_A, 2, char_lcd::PORT_CONTROL),
+                    // busy flag and data reads
+                    wire(2, char_lcd::PORT_DATA, 3, via::PORT_B),
+                ],
+            },
+
+            MachineProfile::Apple1 => MachineConfig {
+                cpu_variant: CpuVariant::Nmos6502,
+                start_pc: StartPc::ResetVector,
+
+                devices: vec![
+                    device(DeviceId::Ram, 1, 0x0000, 0x1000),
+                    DeviceConfig {
+                        mirror: Some(0x1000),
+                        ..device(DeviceId::Pia, 2, 0xD000, 0)
+                    },
+                    device(DeviceId::Rom, 3, 0xFF00, 0x100),
+                ],
+
+                wires: Vec::new(),
+            },
+        }
+    }
+}
+
+fn device(id: DeviceId, uid: u16, start: u16, size: u16) -> DeviceConfig {
+    DeviceConfig {
+        type_name: id.name().to_string(),
+        uid,
+
+        start,
+        size,
+
+        mirror: None,
+        contents: None,
+    }
+}
+
+fn wire(from_uid: u16, from_port: u8, to_uid: u16, to_port: u8) -> PortWire {
+    PortWire {
+        from_uid,
+        from_port,
+
+        to_uid,
+        to_port,
+    }
+}
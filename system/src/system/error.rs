@@ -0,0 +1,109 @@
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsValue;
+
+/// Kinds of `SystemError`, available as the `kind` property of the JS errors.
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum SystemErrorKind {
+    InvalidSize = 0,
+    RangeOverflow = 1,
+    RangeOverlap = 2,
+    DuplicatedUid = 3,
+    BadIndex = 4,
+    CpuNotRemovable = 5,
+    UnknownDeviceType = 6,
+    InvalidExpression = 7,
+    NotResizable = 8,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum SystemError {
+    /// The size is 0 for a device without a fixed size, or it isn't 0 for a device with one.
+    InvalidSize { type_name: String, size: u16 },
+
+    /// The device would end past the address $FFFF.
+    RangeOverflow { start: u16, size: u32 },
+
+    /// The device would share addresses with the device `other_uid`.
+    RangeOverlap { start: u16, end: u32, other_uid: u16 },
+
+    DuplicatedUid(u16),
+
+    /// There is no device with that index.
+    BadIndex(usize),
+
+    /// The CPU (index 0) can't be removed or moved.
+    CpuNotRemovable,
+
+    /// There is no device type with that name, or it can't be created by the user.
+    UnknownDeviceType(String),
+
+    /// A breakpoint condition or log message has an expression that can't be parsed, `expr` is
+    /// its text and `reason` describes the problem.
+    InvalidExpression { expr: String, reason: String },
+
+    /// Only memory devices can be resized, the state of the other ones would be lost.
+    NotResizable(String),
+}
+
+impl SystemError {
+    pub fn kind(&self) -> SystemErrorKind {
+        match self {
+            SystemError::InvalidSize { .. } => SystemErrorKind::InvalidSize,
+            SystemError::RangeOverflow { .. } => SystemErrorKind::RangeOverflow,
+            SystemError::RangeOverlap { .. } => SystemErrorKind::RangeOverlap,
+            SystemError::DuplicatedUid(_) => SystemErrorKind::DuplicatedUid,
+            SystemError::BadIndex(_) => SystemErrorKind::BadIndex,
+            SystemError::CpuNotRemovable => SystemErrorKind::CpuNotRemovable,
+            SystemError::UnknownDeviceType(_) => SystemErrorKind::UnknownDeviceType,
+            SystemError::InvalidExpression { .. } => SystemErrorKind::InvalidExpression,
+            SystemError::NotResizable(_) => SystemErrorKind::NotResizable,
+        }
+    }
+}
+
+impl fmt::Display for SystemError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SystemError::InvalidSize { type_name, size: 0 } => {
+                write!(f, "a {} device needs a size", type_name)
+            }
+
+            SystemError::InvalidSize { type_name, size } => {
+                write!(f, "a {} device has a fixed size, it can't have a size of {:#06X}", type_name, size)
+            }
+
+            SystemError::RangeOverflow { start, size } => {
+                write!(f, "{:#06X} bytes starting at {:#06X} don't fit in the address space", size, start)
+            }
+
+            SystemError::RangeOverlap { start, end, other_uid } => {
+                write!(f, "the range {:#06X}-{:#06X} overlaps with the device {}", start, end - 1, other_uid)
+            }
+
+            SystemError::DuplicatedUid(uid) => write!(f, "there is already a device with the uid {}", uid),
+            SystemError::BadIndex(index) => write!(f, "there is no device with the index {}", index),
+            SystemError::CpuNotRemovable => write!(f, "the CPU can't be removed or moved"),
+            SystemError::UnknownDeviceType(name) => write!(f, "unknown device type \"{}\"", name),
+            SystemError::InvalidExpression { expr, reason } => write!(f, "invalid expression \"{}\": {}", expr, reason),
+            SystemError::NotResizable(name) => write!(f, "a {} device can't be resized, only RAM and ROM can", name),
+        }
+    }
+}
+
+/// Converts the error to a JS `Error` with the message, `name` set to "SystemError" and a `kind`
+/// property with its `SystemErrorKind`.
+impl From<SystemError> for JsValue {
+    fn from(error: SystemError) -> Self {
+        let js_error = js_sys::Error::new(&error.to_string());
+        js_error.set_name("SystemError");
+
+        // it can only fail if the object is frozen
+        let _ = js_sys::Reflect::set(&js_error, &JsValue::from_str("kind"), &JsValue::from(error.kind() as u8));
+
+        js_error.into()
+    }
+}
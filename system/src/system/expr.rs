@@ -0,0 +1,391 @@
+use std::convert::TryFrom;
+
+use crate::cpu::{CPU, CpuRegister, CpuFlag};
+
+use super::MemManager;
+
+// Expressions over the cpu registers, flags and memory, for conditional breakpoints and logpoints.
+//
+//  numbers     255, $FF, 0xFF, %11111111
+//  registers   A, X, Y, S, P, PC
+//  flags       C, Z, I, D, V, N (1 if set)
+//  memory      mem[addr] (a byte), word[addr] (16 bits little endian)
+//  operators   unary - ! ~, * / %, + -, << >>, < <= > >=, == !=, &, ^, |, &&, || (C precedence)
+//
+// Names are case insensitive, comparisons and logic operators give 1 or 0. Memory is read
+// without side effects (see `MemManager::peek`), and a division by 0 gives 0. A '%' where an
+// operand is expected starts a binary number, anywhere else it's the modulo operator.
+
+/// Operators and brackets nested at most, deeper expressions are rejected so parsing and
+/// evaluating them can't overflow the stack.
+const MAX_DEPTH: usize = 100;
+
+/// Operators by precedence, from the lowest.
+const BINARY_LEVELS: [&[BinaryOp]; 10] = [
+    &[BinaryOp::Or],
+    &[BinaryOp::And],
+    &[BinaryOp::BitOr],
+    &[BinaryOp::BitXor],
+    &[BinaryOp::BitAnd],
+    &[BinaryOp::Eq, BinaryOp::Ne],
+    &[BinaryOp::Lt, BinaryOp::Le, BinaryOp::Gt, BinaryOp::Ge],
+    &[BinaryOp::Shl, BinaryOp::Shr],
+    &[BinaryOp::Add, BinaryOp::Sub],
+    &[BinaryOp::Mul, BinaryOp::Div, BinaryOp::Rem],
+];
+
+/// Symbols of the operators, the longest ones first so they are matched before their prefixes.
+const SYMBOLS: [&str; 24] = [
+    "&&", "||", "==", "!=", "<=", ">=", "<<", ">>",
+    "<", ">", "+", "-", "*", "/", "%", "&", "|", "^", "!", "~",
+    "(", ")", "[", "]",
+];
+
+#[derive(Copy, Clone, PartialEq)]
+enum BinaryOp {
+    Or, And,
+    BitOr, BitXor, BitAnd,
+    Eq, Ne,
+    Lt, Le, Gt, Ge,
+    Shl, Shr,
+    Add, Sub,
+    Mul, Div, Rem,
+}
+
+impl BinaryOp {
+    fn symbol(&self) -> &'static str {
+        match self {
+            BinaryOp::Or => "||",
+            BinaryOp::And => "&&",
+            BinaryOp::BitOr => "|",
+            BinaryOp::BitXor => "^",
+            BinaryOp::BitAnd => "&",
+            BinaryOp::Eq => "==",
+            BinaryOp::Ne => "!=",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+            BinaryOp::Shl => "<<",
+            BinaryOp::Shr => ">>",
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Rem => "%",
+        }
+    }
+
+    fn apply(&self, a: i64, b: i64) -> i64 {
+        match self {
+            BinaryOp::Or => (a != 0 || b != 0) as i64,
+            BinaryOp::And => (a != 0 && b != 0) as i64,
+            BinaryOp::BitOr => a | b,
+            BinaryOp::BitXor => a ^ b,
+            BinaryOp::BitAnd => a & b,
+            BinaryOp::Eq => (a == b) as i64,
+            BinaryOp::Ne => (a != b) as i64,
+            BinaryOp::Lt => (a < b) as i64,
+            BinaryOp::Le => (a <= b) as i64,
+            BinaryOp::Gt => (a > b) as i64,
+            BinaryOp::Ge => (a >= b) as i64,
+            BinaryOp::Shl => u32::try_from(b).ok().and_then(|b| a.checked_shl(b)).unwrap_or(0),
+            BinaryOp::Shr => u32::try_from(b).ok().and_then(|b| a.checked_shr(b)).unwrap_or(0),
+            BinaryOp::Add => a.wrapping_add(b),
+            BinaryOp::Sub => a.wrapping_sub(b),
+            BinaryOp::Mul => a.wrapping_mul(b),
+            BinaryOp::Div => a.checked_div(b).unwrap_or(0),
+            BinaryOp::Rem => a.checked_rem(b).unwrap_or(0),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum UnaryOp {
+    Neg,
+    Not,
+    BitNot,
+}
+
+enum Node {
+    Num(i64),
+    Register(CpuRegister),
+    Flag(CpuFlag),
+    Byte(Box<Node>),
+    Word(Box<Node>),
+    Unary(UnaryOp, Box<Node>),
+    Binary(BinaryOp, Box<Node>, Box<Node>),
+}
+
+impl Node {
+    fn eval(&self, cpu: &CPU, mem: &MemManager) -> i64 {
+        match self {
+            Node::Num(value) => *value,
+            Node::Register(reg) => cpu.register(*reg) as i64,
+            Node::Flag(flag) => cpu.flag(*flag) as i64,
+
+            Node::Byte(addr) => mem.peek(addr.eval(cpu, mem) as u16) as i64,
+
+            Node::Word(addr) => {
+                let addr = addr.eval(cpu, mem) as u16;
+
+                u16::from_le_bytes([mem.peek(addr), mem.peek(addr.wrapping_add(1))]) as i64
+            }
+
+            Node::Unary(op, value) => {
+                let value = value.eval(cpu, mem);
+
+                match op {
+                    UnaryOp::Neg => value.wrapping_neg(),
+                    UnaryOp::Not => (value == 0) as i64,
+                    UnaryOp::BitNot => !value,
+                }
+            }
+
+            Node::Binary(op, a, b) => op.apply(a.eval(cpu, mem), b.eval(cpu, mem)),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+enum Token {
+    Num(i64),
+    Name(String),
+    Symbol(&'static str),
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = text.trim_start();
+
+    while let Some(c) = rest.chars().next() {
+        if c == '%' && !ends_operand(tokens.last()) && rest[1..].starts_with(['0', '1']) {
+            let len = rest[1..].find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .map_or(rest.len(), |len| len + 1);
+
+            let value = i64::from_str_radix(&rest[1..len], 2)
+                .map_err(|_| format!("invalid binary number \"{}\"", &rest[..len]))?;
+
+            tokens.push(Token::Num(value));
+            rest = &rest[len..];
+        } else if let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) {
+            tokens.push(Token::Symbol(symbol));
+            rest = &rest[symbol.len()..];
+        } else if c.is_ascii_alphanumeric() || c == '$' || c == '_' {
+            let len = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '$' || c == '_'))
+                .unwrap_or(rest.len());
+
+            tokens.push(word_token(&rest[..len])?);
+            rest = &rest[len..];
+        } else {
+            return Err(format!("unexpected character '{}'", c));
+        }
+
+        rest = rest.trim_start();
+    }
+
+    Ok(tokens)
+}
+
+/// True if `token` can be the end of an operand, so a '%' after it is the modulo operator.
+fn ends_operand(token: Option<&Token>) -> bool {
+    matches!(token, Some(Token::Num(_)) | Some(Token::Name(_)) | Some(Token::Symbol(")")) | Some(Token::Symbol("]")))
+}
+
+fn word_token(word: &str) -> Result<Token, String> {
+    let first = word.chars().next().unwrap_or_default();
+
+    if !first.is_ascii_digit() && first != '$' {
+        return Ok(Token::Name(word.to_ascii_uppercase()));
+    }
+
+    let lower = word.to_ascii_lowercase();
+
+    let parsed = if let Some(hex) = lower.strip_prefix('$').or_else(|| lower.strip_prefix("0x")) {
+        i64::from_str_radix(hex, 16)
+    } else {
+        lower.parse()
+    };
+
+    parsed
+        .map(Token::Num)
+        .map_err(|_| format!("invalid number \"{}\"", word))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+
+    /// Operators and brackets being parsed, see `MAX_DEPTH`.
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+
+        token
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), String> {
+        match self.next() {
+            Some(Token::Symbol(found)) if found == symbol => Ok(()),
+            _ => Err(format!("expected '{}'", symbol)),
+        }
+    }
+
+    fn enter(&mut self) -> Result<(), String> {
+        self.depth += 1;
+
+        if self.depth > MAX_DEPTH {
+            Err("the expression is nested too deeply".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Node, String> {
+        if level == BINARY_LEVELS.len() {
+            return self.unary();
+        }
+
+        let mut node = self.binary(level + 1)?;
+        let depth = self.depth;
+
+        loop {
+            let op = match self.peek() {
+                Some(Token::Symbol(symbol)) => BINARY_LEVELS[level].iter().find(|op| op.symbol() == *symbol),
+                _ => None,
+            };
+
+            match op {
+                Some(op) => {
+                    self.pos += 1;
+
+                    // the previous operations are nested in this one
+                    self.enter()?;
+
+                    let rhs = self.binary(level + 1)?;
+                    node = Node::Binary(*op, Box::new(node), Box::new(rhs));
+                }
+
+                None => {
+                    self.depth = depth;
+
+                    return Ok(node);
+                }
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<Node, String> {
+        let op = match self.peek() {
+            Some(Token::Symbol("-")) => Some(UnaryOp::Neg),
+            Some(Token::Symbol("!")) => Some(UnaryOp::Not),
+            Some(Token::Symbol("~")) => Some(UnaryOp::BitNot),
+            _ => None,
+        };
+
+        match op {
+            Some(op) => {
+                self.pos += 1;
+
+                self.enter()?;
+                let value = self.unary()?;
+                self.depth -= 1;
+
+                Ok(Node::Unary(op, Box::new(value)))
+            }
+
+            None => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Node, String> {
+        match self.next() {
+            Some(Token::Num(value)) => Ok(Node::Num(value)),
+
+            Some(Token::Symbol("(")) => {
+                self.enter()?;
+                let node = self.binary(0)?;
+                self.expect(")")?;
+                self.depth -= 1;
+
+                Ok(node)
+            }
+
+            Some(Token::Name(name)) => self.name(&name),
+
+            Some(Token::Symbol(symbol)) => Err(format!("unexpected '{}'", symbol)),
+            None => Err("unexpected end of the expression".to_string()),
+        }
+    }
+
+    fn name(&mut self, name: &str) -> Result<Node, String> {
+        let node = match name {
+            "A" => Node::Register(CpuRegister::A),
+            "X" => Node::Register(CpuRegister::X),
+            "Y" => Node::Register(CpuRegister::Y),
+            "S" => Node::Register(CpuRegister::S),
+            "P" => Node::Register(CpuRegister::P),
+            "PC" => Node::Register(CpuRegister::Pc),
+
+            "C" => Node::Flag(CpuFlag::Carry),
+            "Z" => Node::Flag(CpuFlag::Zero),
+            "I" => Node::Flag(CpuFlag::Interrupt),
+            "D" => Node::Flag(CpuFlag::Decimal),
+            "V" => Node::Flag(CpuFlag::Overflow),
+            "N" => Node::Flag(CpuFlag::Negative),
+
+            "MEM" | "WORD" => {
+                self.expect("[")?;
+                self.enter()?;
+                let addr = Box::new(self.binary(0)?);
+                self.expect("]")?;
+                self.depth -= 1;
+
+                if name == "MEM" { Node::Byte(addr) } else { Node::Word(addr) }
+            }
+
+            _ => return Err(format!("unknown name \"{}\"", name)),
+        };
+
+        Ok(node)
+    }
+}
+
+/// A parsed expression, see the top of this file for the syntax.
+pub struct Expr {
+    root: Node,
+}
+
+impl Expr {
+    /// Parses `text`, the error is a description of the problem.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            pos: 0,
+
+            depth: 0,
+        };
+
+        let root = parser.binary(0)?;
+
+        match parser.peek() {
+            None => Ok(Expr { root }),
+
+            Some(Token::Num(value)) => Err(format!("unexpected {}", value)),
+            Some(Token::Name(name)) => Err(format!("unexpected \"{}\"", name)),
+            Some(Token::Symbol(symbol)) => Err(format!("unexpected '{}'", symbol)),
+        }
+    }
+
+    pub fn eval(&self, cpu: &CPU, mem: &MemManager) -> i64 {
+        self.root.eval(cpu, mem)
+    }
+}
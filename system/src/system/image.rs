@@ -0,0 +1,315 @@
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+use super::system::ADDRESS_SPACE_SIZE;
+
+// Memory images exchanged with other toolchains and EEPROM programmers.
+//
+// - Raw: the bytes as they are, loaded at a given address, optionally skipping the start of the file.
+// - IntelHex: ":LLAAAATT...CC" text records, the record types 02 and 04 are accepted as long as
+//   the resulting addresses fit in 16 bits.
+// - SRecord: Motorola "S1/S2/S3" text records, with S0 (header), S5/S6 (count) and S7/S8/S9
+//   (termination) records.
+// - Prg: Commodore style, the load address (little endian) followed by the data.
+//
+// The text formats have to end with their end of file/termination record, and every checksum is
+// verified, so truncated or corrupted files are not loaded half way.
+
+#[wasm_bindgen]
+#[derive(Copy, Clone, PartialEq)]
+pub enum ImageFormat {
+    Raw = 0,
+    IntelHex = 1,
+    SRecord = 2,
+    Prg = 3,
+}
+
+pub struct ImageError {
+    /// Line (starting at 1) of the record that caused the error, for the text formats.
+    pub line: Option<usize>,
+
+    pub message: String,
+}
+
+impl ImageError {
+    fn new(line: Option<usize>, message: String) -> Self {
+        ImageError {
+            line,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Contiguous block of data.
+pub struct ImageSegment {
+    pub address: u16,
+    pub data: Vec<u8>,
+}
+
+pub struct Image {
+    pub segments: Vec<ImageSegment>,
+
+    /// Start address, if the file has one.
+    pub entry: Option<u16>,
+}
+
+impl Image {
+    /// Parses `data` as `format`.
+    ///
+    /// `address` and `skip` are only used by raw images: the file is loaded at `address`, ignoring
+    /// its first `skip` bytes.
+    pub fn parse(format: ImageFormat, data: &[u8], address: u16, skip: usize) -> Result<Image, ImageError> {
+        match format {
+            ImageFormat::Raw => Self::from_raw(data, address, skip),
+            ImageFormat::IntelHex => Self::from_intel_hex(as_text(data)?),
+            ImageFormat::SRecord => Self::from_srecord(as_text(data)?),
+            ImageFormat::Prg => Self::from_prg(data),
+        }
+    }
+
+    pub fn from_raw(data: &[u8], address: u16, skip: usize) -> Result<Image, ImageError> {
+        let data = data.get(skip..).ok_or_else(|| {
+            ImageError::new(None, format!("can't skip {} bytes of a {} bytes file", skip, data.len()))
+        })?;
+
+        Ok(Image {
+            segments: vec![segment(None, address as u32, data)?],
+            entry: None,
+        })
+    }
+
+    pub fn from_prg(data: &[u8]) -> Result<Image, ImageError> {
+        if data.len() < 2 {
+            return Err(ImageError::new(None, "the file is too short to have a load address".to_string()));
+        }
+
+        let address = u16::from_le_bytes([data[0], data[1]]);
+
+        Ok(Image {
+            segments: vec![segment(None, address as u32, &data[2..])?],
+            entry: None,
+        })
+    }
+
+    pub fn from_intel_hex(text: &str) -> Result<Image, ImageError> {
+        let mut segments = Vec::new();
+        let mut entry = None;
+
+        let mut base = 0_u32;
+        let mut ended = false;
+
+        for (num, line) in records(text) {
+            if ended {
+                return Err(ImageError::new(Some(num), "record after the end of file record".to_string()));
+            }
+
+            let bytes = line.strip_prefix(':')
+                .ok_or_else(|| ImageError::new(Some(num), "records must start with ':'".to_string()))
+                .and_then(|digits| hex_bytes(num, digits))?;
+
+            if bytes.len() < 5 || bytes.len() != 5 + bytes[0] as usize {
+                return Err(ImageError::new(Some(num), "the record length doesn't match its byte count".to_string()));
+            }
+
+            let sum = bytes.iter().fold(0_u8, |sum, byte| sum.wrapping_add(*byte));
+
+            if sum != 0 {
+                let found = bytes[bytes.len() - 1];
+                let expected = found.wrapping_sub(sum);
+
+                return Err(checksum_error(num, expected, found));
+            }
+
+            let offset = u16::from_be_bytes([bytes[1], bytes[2]]) as u32;
+            let record_type = bytes[3];
+            let data = &bytes[4..bytes.len() - 1];
+
+            match (record_type, data.len()) {
+                (0x00, _) => segments.push(segment(Some(num), base + offset, data)?),
+                (0x01, _) => ended = true,
+
+                (0x02, 2) => base = (u16::from_be_bytes([data[0], data[1]]) as u32) << 4,
+                (0x04, 2) => base = (u16::from_be_bytes([data[0], data[1]]) as u32) << 16,
+
+                // 03: CS:IP, 05: linear start address
+                (0x03, 4) => {
+                    let cs = u16::from_be_bytes([data[0], data[1]]) as u32;
+                    let ip = u16::from_be_bytes([data[2], data[3]]) as u32;
+
+                    entry = Some(entry_address(num, (cs << 4) + ip)?);
+                }
+
+                (0x05, 4) => {
+                    let address = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+
+                    entry = Some(entry_address(num, address)?);
+                }
+
+                (0x02..=0x05, _) => {
+                    return Err(ImageError::new(Some(num), format!("invalid length for a type {:02X} record", record_type)));
+                }
+
+                _ => return Err(ImageError::new(Some(num), format!("unknown record type {:02X}", record_type))),
+            }
+        }
+
+        if !ended {
+            return Err(ImageError::new(None, "missing end of file record".to_string()));
+        }
+
+        Ok(Image {
+            segments,
+            entry,
+        })
+    }
+
+    pub fn from_srecord(text: &str) -> Result<Image, ImageError> {
+        let mut segments = Vec::new();
+        let mut entry = None;
+
+        let mut data_records = 0_u32;
+        let mut ended = false;
+
+        for (num, line) in records(text) {
+            if ended {
+                return Err(ImageError::new(Some(num), "record after the termination record".to_string()));
+            }
+
+            let mut chars = line.chars();
+
+            let record_type = match (chars.next(), chars.next()) {
+                (Some('S'), Some(c)) | (Some('s'), Some(c)) => c.to_digit(10)
+                    .ok_or_else(|| ImageError::new(Some(num), format!("unknown record type S{}", c)))?,
+
+                _ => return Err(ImageError::new(Some(num), "records must start with 'S' and their type".to_string())),
+            };
+
+            let bytes = hex_bytes(num, chars.as_str())?;
+
+            if bytes.is_empty() || bytes.len() != 1 + bytes[0] as usize {
+                return Err(ImageError::new(Some(num), "the record length doesn't match its byte count".to_string()));
+            }
+
+            let sum = bytes[..bytes.len() - 1].iter().fold(0_u8, |sum, byte| sum.wrapping_add(*byte));
+            let expected = !sum;
+            let found = bytes[bytes.len() - 1];
+
+            if expected != found {
+                return Err(checksum_error(num, expected, found));
+            }
+
+            let address_len = match record_type {
+                0 | 1 | 5 | 9 => 2,
+                2 | 6 | 8 => 3,
+                3 | 7 => 4,
+
+                _ => return Err(ImageError::new(Some(num), format!("unknown record type S{}", record_type))),
+            };
+
+            let body = &bytes[1..bytes.len() - 1];
+
+            if body.len() < address_len {
+                return Err(ImageError::new(Some(num), "the record is too short for its address".to_string()));
+            }
+
+            let address = body[..address_len].iter().fold(0_u32, |address, byte| (address << 8) | *byte as u32);
+            let data = &body[address_len..];
+
+            match record_type {
+                // header
+                0 => {}
+
+                1..=3 => {
+                    segments.push(segment(Some(num), address, data)?);
+                    data_records += 1;
+                }
+
+                5 | 6 if address != data_records => {
+                    return Err(ImageError::new(
+                        Some(num),
+                        format!("the file has {} data records, but the count record says {}", data_records, address),
+                    ));
+                }
+
+                5 | 6 => {}
+
+                _ => {
+                    entry = Some(entry_address(num, address)?);
+                    ended = true;
+                }
+            }
+        }
+
+        if !ended {
+            return Err(ImageError::new(None, "missing termination record (S7, S8 or S9)".to_string()));
+        }
+
+        Ok(Image {
+            segments,
+            entry,
+        })
+    }
+}
+
+fn as_text(data: &[u8]) -> Result<&str, ImageError> {
+    std::str::from_utf8(data)
+        .map_err(|_| ImageError::new(None, "the file is not text".to_string()))
+}
+
+/// Non empty lines, with their number.
+fn records(text: &str) -> impl Iterator<Item=(usize, &str)> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+}
+
+fn hex_bytes(line: usize, digits: &str) -> Result<Vec<u8>, ImageError> {
+    if !digits.is_ascii() || !digits.len().is_multiple_of(2) {
+        return Err(ImageError::new(Some(line), "expected an even number of hex digits".to_string()));
+    }
+
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&digits[i..i + 2], 16)
+                .map_err(|_| ImageError::new(Some(line), format!("invalid hex byte \"{}\"", &digits[i..i + 2])))
+        })
+        .collect()
+}
+
+fn checksum_error(line: usize, expected: u8, found: u8) -> ImageError {
+    ImageError::new(Some(line), format!("checksum mismatch, expected {:02X} but found {:02X}", expected, found))
+}
+
+fn segment(line: Option<usize>, address: u32, data: &[u8]) -> Result<ImageSegment, ImageError> {
+    if address + data.len() as u32 > ADDRESS_SPACE_SIZE {
+        return Err(ImageError::new(
+            line,
+            format!("{} bytes at ${:X} don't fit in the address space", data.len(), address),
+        ));
+    }
+
+    Ok(ImageSegment {
+        address: address as u16,
+        data: data.to_vec(),
+    })
+}
+
+fn entry_address(line: usize, address: u32) -> Result<u16, ImageError> {
+    if address < ADDRESS_SPACE_SIZE {
+        Ok(address as u16)
+    } else {
+        Err(ImageError::new(Some(line), format!("the start address ${:X} doesn't fit in the address space", address)))
+    }
+}
@@ -1,5 +1,9 @@
-use crate::dev::{DeviceHolder, BoxedDev};
+use std::any::Any;
+
+use crate::dev::{DeviceHolder, BoxedDev, AddressableDeviceTrait, SignalLines};
 use super::{DevHolderVec, Bus};
+use super::{AccessCounters, AccessKind};
+use super::{WriteProvenance, UninitReads};
 
 // The design is kind of weird because i was having trouble with the lack of support for
 // self referencing structs in Rust.
@@ -9,6 +13,39 @@ pub struct MemManager {
     bus: Bus,
 
     devices: DevHolderVec,
+
+    signals: SignalLines,
+
+    wires: Vec<PortWire>,
+
+    /// Writes to this address are recorded in `exit_code`, see `System::set_exit_port`.
+    exit_port: Option<u16>,
+    exit_code: Option<u8>,
+
+    /// Only while counting, see `System::set_access_counters_enabled`.
+    access_counters: Option<AccessCounters>,
+
+    /// Only while recording, see `System::set_write_provenance_enabled`.
+    write_provenance: Option<WriteProvenance>,
+
+    /// Only while detecting them, see `System::set_uninit_reads_enabled`.
+    uninit_reads: Option<UninitReads>,
+
+    /// The instruction being executed and the current cycle, set by `System` so the accesses can
+    /// be attributed to them.
+    access_pc: u16,
+    access_cycle: u64,
+}
+
+/// Connects the output of a device port to the input of another device port (see `DeviceTrait::port_output`),
+/// devices are identified by their uid.
+#[derive(Copy, Clone, PartialEq)]
+pub struct PortWire {
+    pub from_uid: u16,
+    pub from_port: u8,
+
+    pub to_uid: u16,
+    pub to_port: u8,
 }
 
 impl MemManager {
@@ -17,6 +54,22 @@ impl MemManager {
             bus: Bus::new(),
 
             devices: DevHolderVec::new(),
+
+            signals: SignalLines::new(),
+
+            wires: Vec::new(),
+
+            exit_port: None,
+            exit_code: None,
+
+            access_counters: None,
+
+            write_provenance: None,
+
+            uninit_reads: None,
+
+            access_pc: 0,
+            access_cycle: 0,
         }
     }
 
@@ -28,24 +81,176 @@ impl MemManager {
         &mut self.devices
     }
 
+    /// Returns the device at `index` as a `T`, if it exists and is actually a `T`.
+    pub fn device_as<T: AddressableDeviceTrait>(&self, index: usize) -> Option<&T> {
+        self.devices.get(index)
+            .and_then(|holder| {
+                let dev: &dyn Any = holder.device().as_ref();
+
+                dev.downcast_ref::<T>()
+            })
+    }
+
+    /// Returns the device at `index` as a `T`, if it exists and is actually a `T`.
+    pub fn device_as_mut<T: AddressableDeviceTrait>(&mut self, index: usize) -> Option<&mut T> {
+        self.devices.get_mut(index)
+            .and_then(|holder| {
+                let dev: &mut dyn Any = holder.device_mut().as_mut();
+
+                dev.downcast_mut::<T>()
+            })
+    }
+
+    pub fn signals(&self) -> &SignalLines {
+        &self.signals
+    }
+
+    pub fn signals_mut(&mut self) -> &mut SignalLines {
+        &mut self.signals
+    }
+
     pub fn tick(&mut self) {
         self.bus.set_rw(true);
 
+        self.signals.begin_cycle();
+
         for dev in &mut self.devices {
             dev.device_mut().tick();
+            dev.device().drive_signals(&mut self.signals);
+
+            if let Some(exit_code) = dev.device_mut().take_exit_code() {
+                self.exit_code = Some(exit_code);
+            }
         }
+
+        self.propagate_wires();
+
+        self.signals.end_cycle();
+    }
+
+    /// Adds `wire`, returns false if it already exists or any of the devices doesn't.
+    pub fn add_wire(&mut self, wire: PortWire) -> bool {
+        let devices_exist = self.device_index_by_uid(wire.from_uid).is_some()
+            && self.device_index_by_uid(wire.to_uid).is_some();
+
+        if devices_exist && !self.wires.contains(&wire) {
+            self.wires.push(wire);
+
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn remove_wire(&mut self, wire: PortWire) -> bool {
+        let old_len = self.wires.len();
+
+        self.wires.retain(|other| *other != wire);
+
+        self.wires.len() != old_len
+    }
+
+    pub fn wires(&self) -> &[PortWire] {
+        &self.wires
+    }
+
+    // The wires are propagated once per cycle, in the order they were added.
+    fn propagate_wires(&mut self) {
+        for i in 0..self.wires.len() {
+            let wire = self.wires[i];
+
+            let value = self.device_index_by_uid(wire.from_uid)
+                .and_then(|index| self.devices[index].device().port_output(wire.from_port));
+
+            let to_index = self.device_index_by_uid(wire.to_uid);
+
+            if let (Some(value), Some(to_index)) = (value, to_index) {
+                self.devices[to_index].device_mut().set_port_input(wire.to_port, value);
+            }
+        }
+    }
+
+    pub fn set_exit_port(&mut self, addr: Option<u16>) {
+        self.exit_port = addr;
+        self.exit_code = None;
+    }
+
+    pub fn exit_port(&self) -> Option<u16> {
+        self.exit_port
+    }
+
+    /// Returns the last value written to the exit port since the last call, if any.
+    pub fn take_exit_code(&mut self) -> Option<u8> {
+        self.exit_code.take()
+    }
+
+    /// Starts (with all the counts at 0) or stops counting the accesses to every address.
+    pub fn set_access_counters_enabled(&mut self, enabled: bool) {
+        self.access_counters = if enabled { Some(AccessCounters::new()) } else { None };
+    }
+
+    pub fn access_counters(&self) -> Option<&AccessCounters> {
+        self.access_counters.as_ref()
+    }
+
+    /// Starts (with `Some`) or stops recording who wrote every address.
+    pub fn set_write_provenance(&mut self, provenance: Option<WriteProvenance>) {
+        self.write_provenance = provenance;
+    }
+
+    pub fn write_provenance(&self) -> Option<&WriteProvenance> {
+        self.write_provenance.as_ref()
+    }
+
+    /// Starts or stops reporting the reads of uninitialized RAM.
+    pub fn set_uninit_reads_enabled(&mut self, enabled: bool) {
+        self.uninit_reads = if enabled { Some(UninitReads::new()) } else { None };
+    }
+
+    pub fn uninit_reads(&self) -> Option<&UninitReads> {
+        self.uninit_reads.as_ref()
+    }
+
+    pub fn uninit_reads_mut(&mut self) -> Option<&mut UninitReads> {
+        self.uninit_reads.as_mut()
+    }
+
+    /// Sets the address of the instruction the next accesses belong to.
+    pub fn set_access_pc(&mut self, pc: u16) {
+        self.access_pc = pc;
+    }
+
+    #[inline(always)]
+    pub fn set_access_cycle(&mut self, cycle: u64) {
+        self.access_cycle = cycle;
+    }
+
+    pub fn device_index_by_uid(&self, uid: u16) -> Option<usize> {
+        self.devices.iter().position(|holder| holder.uid() == uid)
     }
 }
 
 //utils
 impl MemManager {
+    fn map_addr(&self, addr: u16) -> Option<(&BoxedDev, u16)> {
+        self.devices.iter()
+            .find(|holder| holder.range().contains(&(addr as u32)))
+            .map(|holder| {
+                let offset = (addr as u32 - holder.range().start) % holder.device().size() as u32;
+
+                (holder.device(), offset as u16)
+            })
+    }
+
     fn map_addr_mut(&mut self, addr: u16) -> Option<(&mut BoxedDev, u16)> {
         let mut result = None;
         let iter = &mut self.devices.iter_mut();
 
         while let (Some(holder), None) = (iter.next(), &result) {
-            if holder.range().contains(&addr) {
-                let offset = addr - holder.range().start;
+            if holder.range().contains(&(addr as u32)) {
+                // the range can be bigger than the device, if it's mirrored
+                let offset = (addr as u32 - holder.range().start) % holder.device().size() as u32;
+                let offset = offset as u16;
                 let dev_ref = holder.device_mut();
 
                 result = Some((
@@ -57,13 +262,23 @@ impl MemManager {
         result
     }
 
-    pub fn add_device_unchecked_range(&mut self, dev: BoxedDev, start: u16, end: u16, uid: u16) {
+    pub fn add_device_unchecked_range(&mut self, dev: BoxedDev, start: u16, end: u32, uid: u16) {
         let holden_dev = DeviceHolder::new(dev, start, end, uid);
         self.devices.push(holden_dev);
     }
 
+    /// Removes all the devices and wires.
+    pub fn clear(&mut self) {
+        self.devices.clear();
+        self.wires.clear();
+    }
+
     pub fn remove_device_by(&mut self, index: usize) -> bool {
-        if index < self.devices.capacity() {
+        if let Some(uid) = self.devices.get(index).map(|holder| holder.uid()) {
+            self.wires.retain(|wire| wire.from_uid != uid && wire.to_uid != uid);
+        }
+
+        if index < self.devices.len() {
             self.devices.remove(index);
 
             true
@@ -72,24 +287,24 @@ impl MemManager {
         }
     }
 
-    pub fn device_data_ptr(&mut self, index: usize) -> Option<usize> {
-        self.devices.get_mut(index)
-            .map_or_else(
-                || None,
-                |dev| Some(dev.device_mut().data_ptr() as usize),
-            )
-    }
-
     pub fn reset_devices(&mut self) {
         for dev in &mut self.devices {
             dev.device_mut().reset_system();
         }
+
+        if let Some(uninit_reads) = &mut self.uninit_reads {
+            uninit_reads.reset();
+        }
     }
 
     pub fn reset_devices_hard(&mut self) {
         for dev in &mut self.devices {
             dev.device_mut().reset_hard();
         }
+
+        if let Some(uninit_reads) = &mut self.uninit_reads {
+            uninit_reads.reset();
+        }
     }
 }
 
@@ -113,6 +328,42 @@ impl MemManager {
 
     /// Reads and returns the value in the address `self.addr()`
     pub fn read_at_addr(&mut self) -> u8 {
+        if let Some(counters) = &mut self.access_counters {
+            counters.count(AccessKind::Read, self.bus.addr());
+        }
+
+        if self.uninit_reads.is_some() {
+            self.check_initialized(self.bus.addr(), self.access_pc);
+        }
+
+        self.read_mapped()
+    }
+
+    /// Like `Self::read_at_addr`, for the opcode fetches of the cpu.
+    pub fn fetch_at_addr(&mut self) -> u8 {
+        if let Some(counters) = &mut self.access_counters {
+            counters.count(AccessKind::Execute, self.bus.addr());
+        }
+
+        // the access pc is still the one of the previous instruction, the fetch belongs to the
+        // instruction at its address
+        if self.uninit_reads.is_some() {
+            self.check_initialized(self.bus.addr(), self.bus.addr());
+        }
+
+        self.read_mapped()
+    }
+
+    fn check_initialized(&mut self, addr: u16, pc: u16) {
+        let is_initialized = self.map_addr(addr)
+            .is_none_or(|(dev, offset)| dev.is_initialized(offset));
+
+        if let (false, Some(uninit_reads)) = (is_initialized, &mut self.uninit_reads) {
+            uninit_reads.report(addr, pc, self.access_cycle);
+        }
+    }
+
+    fn read_mapped(&mut self) -> u8 {
         self.bus.set_rw(true);
 
         let mapped = self.map_addr_mut(self.bus.addr());
@@ -135,13 +386,51 @@ impl MemManager {
         // this is needed here because mapped uses a mut ref to self
         let current_data = self.bus.data();
 
+        if let Some(counters) = &mut self.access_counters {
+            counters.count(AccessKind::Write, self.bus.addr());
+        }
+
+        if let Some(provenance) = &mut self.write_provenance {
+            provenance.record(self.bus.addr(), current_data, self.access_pc, self.access_cycle);
+        }
+
+        if self.exit_port == Some(self.bus.addr()) {
+            self.exit_code = Some(current_data);
+        }
+
         let mapped = self.map_addr_mut(self.bus.addr());
 
-        if let Some((dev, offset)) = mapped {
+        let device_exit_code = mapped.and_then(|(dev, offset)| {
             dev.write_unchecked(offset, current_data);
+
+            dev.take_exit_code()
+        });
+
+        if device_exit_code.is_some() {
+            self.exit_code = device_exit_code;
         }
     }
 
+    /// Reads the value at `addr` like the cpu would, but without touching the bus or any device state.
+    ///
+    /// Unmapped addresses read as 0, like in `Self::read_at_addr`.
+    pub fn peek(&self, addr: u16) -> u8 {
+        self.map_addr(addr)
+            .map_or(0, |(dev, offset)| dev.peek_unchecked(offset))
+    }
+
+    pub fn is_mapped(&self, addr: u16) -> bool {
+        self.map_addr(addr).is_some()
+    }
+
+    /// Writes `value` at `addr` with `AddressableDeviceTrait::poke_unchecked`, without touching the bus.
+    ///
+    /// Returns false if the address is not mapped or its device can't be poked.
+    pub fn poke(&mut self, addr: u16, value: u8) -> bool {
+        self.map_addr_mut(addr)
+            .is_some_and(|(dev, offset)| dev.poke_unchecked(offset, value))
+    }
+
     /// Returns the address that the bus in pointing to
     pub fn addr(&self) -> u16 {
         self.bus.addr()
@@ -168,8 +457,9 @@ impl MemManager {
         self.bus.rw()
     }
 
-    /// Resets the bus to 0
+    /// Resets the bus to 0 and releases the signal lines not held by the host
     pub fn reset_bus(&mut self) {
         self.bus.reset();
+        self.signals.reset();
     }
 }
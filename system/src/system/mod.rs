@@ -1,11 +1,34 @@
 mod system;
 mod bus;
 mod mem_manager;
+mod config;
+mod image;
+mod error;
+mod run;
+mod call_stack;
+mod profiler;
+mod access_counters;
+mod write_provenance;
+mod uninit_reads;
+mod expr;
+mod breakpoints;
 
 
 pub use system::System;
 
 use bus::Bus;
-pub use mem_manager::MemManager;
+pub use mem_manager::{MemManager, PortWire};
+pub use config::{MachineConfig, CpuVariant, StartPc, DeviceConfig, DeviceContents, ContentsSource, ConfigError};
+pub use config::MachineProfile;
+pub use image::{Image, ImageSegment, ImageFormat, ImageError};
+pub use error::{SystemError, SystemErrorKind};
+pub use run::{HaltCondition, HaltConditions, StopReason, RunResult};
+pub use call_stack::{CallStack, CallFrame, FrameKind, StackChange, StackMismatch, MismatchKind};
+pub use profiler::{Profiler, SubroutineProfile};
+pub use access_counters::{AccessCounters, AccessKind};
+pub use write_provenance::{WriteProvenance, WriteRecord};
+pub use uninit_reads::{UninitReads, UninitRead};
+pub use expr::Expr;
+pub use breakpoints::Breakpoints;
 
 type DevHolderVec = Vec<crate::dev::DeviceHolder>;
\ No newline at end of file
@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::cpu::OperationStart;
+
+use super::{CallFrame, StackChange};
+
+/// Name of the code that isn't in any subroutine (the one started by the reset).
+const ROOT_NAME: &str = "top";
+
+// Counts executions and cycles for every pc, and cycles for every path of calls ("folded
+// stacks"). The inclusive and exclusive cycles of the subroutines are computed from the paths
+// when a report is made, so running doesn't have to walk the call stack.
+
+pub struct Profiler {
+    /// Times an instruction started at every address.
+    executions: Vec<u32>,
+
+    /// Cycles spent on the instructions started at every address. The cycles of the interrupt
+    /// sequences don't belong to any address.
+    cycles: Vec<u64>,
+
+    /// Times every subroutine (or interrupt handler) was entered, by address.
+    calls: HashMap<u16, u64>,
+
+    /// Cycles spent with every path of calls (the addresses of the called subroutines, from the
+    /// outermost), not counting the current one.
+    stacks: HashMap<Vec<u16>, u64>,
+
+    path: Vec<u16>,
+    path_cycles: u64,
+
+    /// Address of the instruction being executed, if it isn't an interrupt sequence.
+    current_pc: Option<u16>,
+}
+
+/// Cycles spent in a subroutine, see `Profiler::subroutines`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct SubroutineProfile {
+    pub addr: Option<u16>,
+
+    pub calls: u64,
+
+    /// Cycles including the ones of the subroutines it called.
+    pub inclusive: u64,
+
+    /// Cycles of its own instructions.
+    pub exclusive: u64,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Profiler {
+            executions: vec![0; 0x1_0000],
+            cycles: vec![0; 0x1_0000],
+
+            calls: HashMap::new(),
+            stacks: HashMap::new(),
+
+            path: Vec::new(),
+            path_cycles: 0,
+
+            current_pc: None,
+        }
+    }
+
+    /// Called when the cpu starts an operation at `pc`.
+    pub fn operation_started(&mut self, start: OperationStart, pc: u16) {
+        self.current_pc = match start {
+            OperationStart::Opcode(_) => {
+                self.executions[pc as usize] += 1;
+
+                Some(pc)
+            }
+
+            OperationStart::Irq | OperationStart::Nmi => None,
+        };
+    }
+
+    /// Called once every cycle, after the start of the operation of that cycle (if any).
+    #[inline(always)]
+    pub fn cycle(&mut self) {
+        if let Some(pc) = self.current_pc {
+            self.cycles[pc as usize] += 1;
+        }
+
+        self.path_cycles += 1;
+    }
+
+    /// Called when the profiling starts in the middle of a program, or the call stack is cleared.
+    pub fn reset_call_stack(&mut self, frames: &[CallFrame]) {
+        self.flush_path();
+
+        self.path.clear();
+        self.path.extend(frames.iter().map(|frame| frame.target));
+    }
+
+    /// Called when the call stack changes, `frames` being the new one.
+    pub fn set_call_stack(&mut self, change: StackChange, frames: &[CallFrame]) {
+        self.flush_path();
+
+        if let (StackChange::Call, Some(frame)) = (change, frames.last()) {
+            *self.calls.entry(frame.target).or_insert(0) += 1;
+        }
+
+        self.path.clear();
+        self.path.extend(frames.iter().map(|frame| frame.target));
+    }
+
+    fn flush_path(&mut self) {
+        if self.path_cycles > 0 {
+            *self.stacks.entry(self.path.clone()).or_insert(0) += self.path_cycles;
+
+            self.path_cycles = 0;
+        }
+    }
+
+    pub fn executions_at(&self, addr: u16) -> u32 {
+        self.executions[addr as usize]
+    }
+
+    pub fn cycles_at(&self, addr: u16) -> u64 {
+        self.cycles[addr as usize]
+    }
+
+    pub fn total_cycles(&self) -> u64 {
+        self.stacks.values().sum::<u64>() + self.path_cycles
+    }
+
+    /// Cycles spent with every path of calls, including the current one.
+    fn all_stacks(&self) -> Vec<(&[u16], u64)> {
+        let mut stacks: Vec<(&[u16], u64)> = self.stacks.iter()
+            .map(|(path, cycles)| (path.as_slice(), *cycles))
+            .collect();
+
+        if self.path_cycles > 0 {
+            match stacks.iter_mut().find(|(path, _)| *path == self.path.as_slice()) {
+                Some((_, cycles)) => *cycles += self.path_cycles,
+                None => stacks.push((&self.path, self.path_cycles)),
+            }
+        }
+
+        stacks
+    }
+
+    /// The cycles spent in every subroutine, the one with `addr` None is the code outside them,
+    /// sorted by inclusive cycles (descending).
+    pub fn subroutines(&self) -> Vec<SubroutineProfile> {
+        let mut subroutines: HashMap<Option<u16>, SubroutineProfile> = HashMap::new();
+
+        for (path, cycles) in self.all_stacks() {
+            self.subroutine_entry(&mut subroutines, None).inclusive += cycles;
+            self.subroutine_entry(&mut subroutines, path.last().copied()).exclusive += cycles;
+
+            // a recursive subroutine counts once per path
+            for (i, addr) in path.iter().enumerate() {
+                if !path[..i].contains(addr) {
+                    self.subroutine_entry(&mut subroutines, Some(*addr)).inclusive += cycles;
+                }
+            }
+        }
+
+        let mut subroutines: Vec<SubroutineProfile> = subroutines.into_values().collect();
+        subroutines.sort_by(|a, b| b.inclusive.cmp(&a.inclusive).then(a.addr.cmp(&b.addr)));
+
+        subroutines
+    }
+
+    fn subroutine_entry<'a>(
+        &self, subroutines: &'a mut HashMap<Option<u16>, SubroutineProfile>, addr: Option<u16>,
+    ) -> &'a mut SubroutineProfile {
+        subroutines.entry(addr).or_insert_with(|| SubroutineProfile {
+            addr,
+            calls: addr.and_then(|addr| self.calls.get(&addr)).copied().unwrap_or(0),
+            inclusive: 0,
+            exclusive: 0,
+        })
+    }
+
+    /// The profile as text, `name` gives the names of the subroutines (like `$1234`).
+    pub fn report(&self, name: impl Fn(u16) -> String) -> String {
+        let mut text = String::new();
+
+        writeln!(text, "Total cycles: {}", self.total_cycles()).unwrap();
+        writeln!(text).unwrap();
+
+        writeln!(text, "{:<24} {:>10} {:>12} {:>12}", "Subroutine", "Calls", "Inclusive", "Exclusive").unwrap();
+
+        for subroutine in self.subroutines() {
+            let subroutine_name = subroutine.addr.map_or_else(|| ROOT_NAME.to_string(), &name);
+
+            writeln!(
+                text, "{:<24} {:>10} {:>12} {:>12}",
+                subroutine_name, subroutine.calls, subroutine.inclusive, subroutine.exclusive
+            ).unwrap();
+        }
+
+        writeln!(text).unwrap();
+
+        writeln!(text, "{:<8} {:>10} {:>12}", "Address", "Executions", "Cycles").unwrap();
+
+        let mut addresses: Vec<usize> = (0..self.cycles.len())
+            .filter(|addr| self.executions[*addr] > 0)
+            .collect();
+
+        addresses.sort_by(|a, b| self.cycles[*b].cmp(&self.cycles[*a]).then(a.cmp(b)));
+
+        for addr in addresses {
+            writeln!(text, "${:04X}    {:>10} {:>12}", addr, self.executions[addr], self.cycles[addr]).unwrap();
+        }
+
+        text
+    }
+
+    /// The cycles of every path of calls in the "folded stacks" format used by flamegraph tools,
+    /// one `top;name;name cycles` line per path. `name` gives the names of the subroutines.
+    pub fn folded_stacks(&self, name: impl Fn(u16) -> String) -> String {
+        let mut lines: Vec<String> = self.all_stacks().into_iter()
+            .map(|(path, cycles)| {
+                let mut line = ROOT_NAME.to_string();
+
+                for addr in path {
+                    line.push(';');
+                    line += &name(*addr);
+                }
+
+                format!("{} {}", line, cycles)
+            })
+            .collect();
+
+        lines.sort();
+
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
+}
+
+/// Default name of the subroutine at `addr`.
+pub fn address_name(addr: u16) -> String {
+    format!("${:04X}", addr)
+}
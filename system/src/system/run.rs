@@ -0,0 +1,152 @@
+use wasm_bindgen::prelude::*;
+
+use crate::cpu::CpuFlag;
+
+// Conditions checked by `System::run` before every instruction, they only look at the instruction
+// about to be executed (with `System::peek`), so they don't depend on how it's implemented.
+
+const OPCODE_BRK: u8 = 0x00;
+const OPCODE_JMP_ABS: u8 = 0x4C;
+
+pub(super) const OPCODE_JSR: u8 = 0x20;
+pub(super) const OPCODE_RTS: u8 = 0x60;
+pub(super) const OPCODE_RTI: u8 = 0x40;
+
+/// Opcodes that jam the NMOS 6502.
+const OPCODES_KIL: [u8; 12] = [0x02, 0x12, 0x22, 0x32, 0x42, 0x52, 0x62, 0x72, 0x92, 0xB2, 0xD2, 0xF2];
+
+/// Branch offset that jumps to the branch itself.
+const BRANCH_TO_SELF: u8 = 0xFE;
+
+#[wasm_bindgen]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum HaltCondition {
+    /// A `JMP` to itself, or a branch to itself that is going to be taken.
+    ///
+    /// Programs waiting for interrupts this way need it disabled.
+    JumpToSelf = 0,
+
+    /// A `BRK`, for programs that don't use interrupts.
+    Brk = 1,
+
+    /// One of the opcodes that jam the cpu (`KIL`, also known as `JAM`).
+    Kil = 2,
+}
+
+#[wasm_bindgen]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum StopReason {
+    /// `System::run` executed the given amount of cycles.
+    CycleLimit = 0,
+
+    JumpToSelf = 1,
+    Brk = 2,
+    Kil = 3,
+
+    /// The program wrote to the exit port (see `System::set_exit_port`), or asked a device to exit
+    /// (see `DeviceTrait::take_exit_code`).
+    ExitPort = 4,
+
+    /// `System::step_over`, `System::step_out` or `System::run_to` got where they were going.
+    TargetReached = 5,
+
+    /// A breakpoint fired (see `System::set_breakpoint`).
+    Breakpoint = 6,
+}
+
+/// Result of `System::run`.
+#[wasm_bindgen]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct RunResult {
+    pub reason: StopReason,
+
+    /// The value written to the exit port for `StopReason::ExitPort`, the A register for the
+    /// other halt conditions, and 0 for `StopReason::CycleLimit`, `StopReason::TargetReached` and
+    /// `StopReason::Breakpoint`.
+    pub exit_code: u8,
+
+    /// Cycles executed by this run.
+    pub cycles: u32,
+}
+
+/// Enabled `HaltCondition`s, by default only `HaltCondition::Kil`, as programs that use interrupts
+/// often wait for them with a jump to itself, and BRK might be handled by them too.
+#[derive(Copy, Clone, PartialEq)]
+pub struct HaltConditions {
+    pub jump_to_self: bool,
+    pub brk: bool,
+    pub kil: bool,
+}
+
+impl Default for HaltConditions {
+    fn default() -> Self {
+        HaltConditions {
+            jump_to_self: false,
+            brk: false,
+            kil: true,
+        }
+    }
+}
+
+impl HaltConditions {
+    pub fn set(&mut self, condition: HaltCondition, enabled: bool) {
+        match condition {
+            HaltCondition::JumpToSelf => self.jump_to_self = enabled,
+            HaltCondition::Brk => self.brk = enabled,
+            HaltCondition::Kil => self.kil = enabled,
+        }
+    }
+
+    pub fn is_enabled(&self, condition: HaltCondition) -> bool {
+        match condition {
+            HaltCondition::JumpToSelf => self.jump_to_self,
+            HaltCondition::Brk => self.brk,
+            HaltCondition::Kil => self.kil,
+        }
+    }
+
+    /// Returns the reason to stop before executing the instruction at `pc`, if any.
+    ///
+    /// `peek` reads memory without side effects, `flag` returns the current value of a flag.
+    pub fn check(&self, pc: u16, peek: impl Fn(u16) -> u8, flag: impl Fn(CpuFlag) -> bool) -> Option<StopReason> {
+        let opcode = peek(pc);
+
+        if self.kil && OPCODES_KIL.contains(&opcode) {
+            return Some(StopReason::Kil);
+        }
+
+        if self.brk && opcode == OPCODE_BRK {
+            return Some(StopReason::Brk);
+        }
+
+        if self.jump_to_self && jumps_to_self(pc, opcode, &peek, &flag) {
+            return Some(StopReason::JumpToSelf);
+        }
+
+        None
+    }
+}
+
+fn jumps_to_self(pc: u16, opcode: u8, peek: &impl Fn(u16) -> u8, flag: &impl Fn(CpuFlag) -> bool) -> bool {
+    if opcode == OPCODE_JMP_ABS {
+        let target = u16::from_le_bytes([peek(pc.wrapping_add(1)), peek(pc.wrapping_add(2))]);
+
+        return target == pc;
+    }
+
+    // branches are xxy10000, xx selects the flag and y the value that takes the branch
+    if opcode & 0x1F != 0x10 || peek(pc.wrapping_add(1)) != BRANCH_TO_SELF {
+        return false;
+    }
+
+    let tested_flag = match opcode >> 6 {
+        0 => CpuFlag::Negative,
+        1 => CpuFlag::Overflow,
+        2 => CpuFlag::Carry,
+        _ => CpuFlag::Zero,
+    };
+
+    let taken_if = (opcode & 0x20) != 0;
+
+    flag(tested_flag) == taken_if
+}
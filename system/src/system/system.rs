@@ -1,16 +1,65 @@
+use std::any::Any;
+use std::cell::Cell;
+use std::rc::Rc;
+
 use wasm_bindgen::prelude::wasm_bindgen;
-use js_sys::Map;
+use wasm_bindgen::JsValue;
+use js_sys::{Map, Array, Uint8Array};
+
+use super::{MemManager, PortWire};
+use super::{MachineConfig, CpuVariant, StartPc, DeviceConfig, DeviceContents, ContentsSource, ConfigError};
+use super::MachineProfile;
+use super::{Image, ImageFormat, ImageError};
+use super::SystemError;
+use super::{HaltCondition, HaltConditions, StopReason, RunResult};
+use super::run::{OPCODE_JSR, OPCODE_RTS, OPCODE_RTI};
+use super::{CallStack, CallFrame, FrameKind, StackChange, StackMismatch};
+use super::Profiler;
+use super::AccessKind;
+use super::{WriteProvenance, WriteRecord};
+use super::UninitRead;
+use super::{Breakpoints, Expr};
+use super::profiler::address_name;
 
-use super::MemManager;
+use crate::cpu::{CPU, CpuRegister, CpuFlag};
+use crate::dev::{BoxedDev, DeviceId, DeviceFactory, DeviceRegistry, DeviceSize, SignalLine, DeviceRepresentation, DeviceRepresentationFactory, DeviceTrait, PAGE_SIZE};
+use crate::dev::mem::{Ram, RamInit, RamInitKind};
+use crate::dev::io::{CharLcd, KeyboardMatrix, Joystick, KeyLatch, Pia, SoundGenerator, Prng, RealTimeClock, Acia, SerialBackend, BufferSerial, Semihost};
 
-use crate::cpu::CPU;
-use crate::dev::{DeviceId, DeviceFactory, DeviceRepresentation, DeviceRepresentationFactory, DeviceTrait};
+/// Devices can be mapped up to (and including) the address 0xFFFF.
+pub(super) const ADDRESS_SPACE_SIZE: u32 = 0x1_0000;
 
 #[wasm_bindgen]
 pub struct System {
     cpu: CPU,
 
     mem: MemManager,
+
+    registry: DeviceRegistry,
+
+    halt_conditions: HaltConditions,
+
+    /// Cycles since the last reset.
+    cycles: u64,
+
+    /// `Self::cycles`, shared with the devices that need it (see `Self::connect_device`).
+    clock: Rc<Cell<u64>>,
+
+    call_stack: CallStack,
+
+    /// Only while profiling, see `Self::set_profiler_enabled`.
+    profiler: Option<Profiler>,
+
+    breakpoints: Breakpoints,
+
+    /// Given to every RAM device, see `Self::set_ram_init`.
+    ram_init: RamInit,
+}
+
+impl Default for System {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[wasm_bindgen]
@@ -21,34 +70,428 @@ impl System {
             cpu: CPU::new(),
 
             mem: MemManager::new(),
+
+            registry: DeviceRegistry::new(),
+
+            halt_conditions: HaltConditions::default(),
+
+            cycles: 0,
+            clock: Rc::new(Cell::new(0)),
+
+            call_stack: CallStack::new(),
+
+            profiler: None,
+
+            breakpoints: Breakpoints::new(),
+
+            ram_init: RamInit::Zero,
         }
     }
 
     pub fn tick(&mut self) {
+        self.clock.set(self.cycles);
+
         self.mem.tick(); //tick the bus and all the devices
 
+        // the cpu resets in its tick
+        if self.mem.signals().is_asserted(SignalLine::Reset) {
+            self.clear_call_stack();
+        }
+
+        let s = self.cpu.register(CpuRegister::S) as u8;
+
+        self.mem.set_access_cycle(self.cycles);
+
         self.cpu.tick_with_mem(&mut self.mem);
-    }
 
-    pub fn execute_operation(&mut self) {
-        let mut continue_execution = true;
+        if let Some((start, pc)) = self.cpu.take_started_operation() {
+            self.call_stack.operation_started(start, pc, s);
+
+            // set after the opcode fetch, so a fetch belongs to the instruction before it (the one
+            // that jumped there), the writes and other reads come in the next cycles
+            self.mem.set_access_pc(pc);
 
-        while continue_execution {
+            if let Some(profiler) = &mut self.profiler {
+                profiler.operation_started(start, pc);
+            }
+        }
+
+        if let Some(profiler) = &mut self.profiler {
+            profiler.cycle();
+        }
+
+        if self.cpu.is_between_instructions() {
+            let change = self.call_stack.operation_done(
+                self.cpu.register(CpuRegister::Pc), self.cpu.register(CpuRegister::S) as u8,
+            );
+
+            if let (StackChange::Call | StackChange::Return, Some(profiler)) = (change, &mut self.profiler) {
+                profiler.set_call_stack(change, self.call_stack.frames());
+            }
+        }
+
+        self.cycles += 1;
+    }
+
+    /// Executes the rest of the current operation.
+    ///
+    /// Returns false if it stopped in the middle of it, after a cycle with RDY held low by a device
+    /// (the cpu waits for it), the next call continues the same operation.
+    pub fn execute_operation(&mut self) -> bool {
+        loop {
             self.tick();
 
-            continue_execution = !self.cpu.operation_is_done();
+            if self.cpu.operation_is_done() {
+                return true;
+            }
+
+            if self.mem.signals().is_asserted(SignalLine::Rdy) {
+                return false;
+            }
         }
     }
 
-    pub fn execute_operation_x(&mut self, amm: i32) {
-        for _ in 0..amm {
-            self.execute_operation();
+    /// Executes `amm` operations, see `Self::execute_operation`.
+    ///
+    /// Stops at the first one that RDY doesn't let finish, returns how many were finished.
+    pub fn execute_operation_x(&mut self, amm: i32) -> i32 {
+        for done in 0..amm {
+            if !self.execute_operation() {
+                return done;
+            }
         }
+
+        amm.max(0)
+    }
+
+    /// Runs until a halt condition is found (see `Self::set_halt_condition` and `Self::set_exit_port`),
+    /// or until `max_cycles` are executed.
+    ///
+    /// The halt conditions and breakpoints (see `Self::set_breakpoint`) are checked before executing
+    /// each instruction, except the first one, so running again continues past the one that stopped it.
+    pub fn run(&mut self, max_cycles: u32) -> RunResult {
+        self.run_until(max_cycles, |_| false)
+    }
+
+    /// Executes the next instruction, if it's a `JSR`, runs until the subroutine returns to the
+    /// instruction after it. Stops earlier like `Self::run`.
+    pub fn step_over(&mut self, max_cycles: u32) -> RunResult {
+        let pc = self.cpu.register(CpuRegister::Pc);
+
+        if self.mem.peek(pc) != OPCODE_JSR {
+            return self.run_until(max_cycles, |_| true);
+        }
+
+        let return_addr = pc.wrapping_add(3);
+        let s = self.cpu.register(CpuRegister::S);
+
+        // the stack check skips the returns of recursive calls to the same subroutine
+        self.run_until(max_cycles, |sys| {
+            sys.cpu.register(CpuRegister::Pc) == return_addr && sys.cpu.register(CpuRegister::S) >= s
+        })
+    }
+
+    /// Runs until the current subroutine (or interrupt handler) returns, that is, until a `RTS` or
+    /// `RTI` leaves the stack above where it was. Stops earlier like `Self::run`.
+    pub fn step_out(&mut self, max_cycles: u32) -> RunResult {
+        let s = self.cpu.register(CpuRegister::S);
+        let mut last_opcode = None;
+
+        self.run_until(max_cycles, move |sys| {
+            let returned = matches!(last_opcode, Some(OPCODE_RTS) | Some(OPCODE_RTI))
+                && sys.cpu.register(CpuRegister::S) > s;
+
+            last_opcode = Some(sys.mem.peek(sys.cpu.register(CpuRegister::Pc)));
+
+            returned
+        })
+    }
+
+    /// Runs until the pc reaches `addr`, executing at least one instruction. Stops earlier like `Self::run`.
+    pub fn run_to(&mut self, addr: u16, max_cycles: u32) -> RunResult {
+        self.run_until(max_cycles, |sys| sys.cpu.register(CpuRegister::Pc) == addr)
+    }
+
+    /// Enables or disables a condition that stops `Self::run`.
+    pub fn set_halt_condition(&mut self, condition: HaltCondition, enabled: bool) {
+        self.halt_conditions.set(condition, enabled);
+    }
+
+    pub fn halt_condition_is_enabled(&self, condition: HaltCondition) -> bool {
+        self.halt_conditions.is_enabled(condition)
+    }
+
+    /// Makes `Self::run` stop when the program writes to `addr`, the value written is the exit code.
+    ///
+    /// The write still reaches the device mapped there, if any.
+    pub fn set_exit_port(&mut self, addr: u16) {
+        self.mem.set_exit_port(Some(addr));
+    }
+
+    pub fn clear_exit_port(&mut self) {
+        self.mem.set_exit_port(None);
+    }
+
+    /// Returns the exit port set with `Self::set_exit_port`, if any.
+    pub fn exit_port(&self) -> Option<u16> {
+        self.mem.exit_port()
+    }
+
+    /// Cycles executed since the last reset.
+    pub fn cycles(&self) -> f64 {
+        self.cycles as f64
+    }
+
+    /// The subroutine calls and interrupts being executed, from the outermost to the innermost.
+    ///
+    /// They are tracked by watching the calls and returns executed since the last reset, see
+    /// `Self::call_stack_take_mismatches` for returns that don't match them.
+    pub fn call_stack(&self) -> Vec<CallFrame> {
+        self.call_stack.frames().to_vec()
+    }
+
+    /// Removes and returns the returns that didn't match the call stack since the last call.
+    pub fn call_stack_take_mismatches(&mut self) -> Vec<StackMismatch> {
+        self.call_stack.take_mismatches()
+    }
+
+    /// Starts (with empty counts) or stops counting the executions and cycles of every address
+    /// and subroutine. The counts are kept across resets, until `Self::profiler_clear`.
+    pub fn set_profiler_enabled(&mut self, enabled: bool) {
+        if enabled == self.profiler.is_some() {
+            return;
+        }
+
+        self.profiler = if enabled { Some(self.new_profiler()) } else { None };
+    }
+
+    pub fn profiler_is_enabled(&self) -> bool {
+        self.profiler.is_some()
+    }
+
+    /// Sets all the profiler counts to 0.
+    pub fn profiler_clear(&mut self) {
+        if self.profiler.is_some() {
+            self.profiler = Some(self.new_profiler());
+        }
+    }
+
+    /// Times an instruction was executed at `addr` while profiling.
+    pub fn profiler_executions_at(&self, addr: u16) -> u32 {
+        self.profiler.as_ref().map_or(0, |profiler| profiler.executions_at(addr))
+    }
+
+    /// Cycles spent on the instructions at `addr` while profiling.
+    pub fn profiler_cycles_at(&self, addr: u16) -> f64 {
+        self.profiler.as_ref().map_or(0.0, |profiler| profiler.cycles_at(addr) as f64)
+    }
+
+    /// The profile as text: the inclusive and exclusive cycles of every subroutine, and the
+    /// executions and cycles of every address. None if the profiler is disabled.
+    ///
+    /// `symbols` maps addresses to names (like the labels of the program), the subroutines at
+    /// other addresses are named by their address.
+    pub fn profiler_report(&self, symbols: &Map) -> Option<String> {
+        self.profiler.as_ref().map(|profiler| profiler.report(|addr| Self::symbol_name(symbols, addr)))
+    }
+
+    /// The cycles of every path of calls in the "folded stacks" format used by flamegraph tools,
+    /// None if the profiler is disabled. The subroutines are named like in `Self::profiler_report`.
+    pub fn profiler_folded_stacks(&self, symbols: &Map) -> Option<String> {
+        self.profiler.as_ref().map(|profiler| profiler.folded_stacks(|addr| Self::symbol_name(symbols, addr)))
+    }
+
+    /// Starts (with all the counts at 0) or stops counting the reads, writes and executions
+    /// (opcode fetches) of every address. The counts are kept across resets.
+    pub fn set_access_counters_enabled(&mut self, enabled: bool) {
+        if enabled != self.access_counters_enabled() {
+            self.mem.set_access_counters_enabled(enabled);
+        }
+    }
+
+    pub fn access_counters_enabled(&self) -> bool {
+        self.mem.access_counters().is_some()
+    }
+
+    /// Sets all the access counts to 0.
+    pub fn access_counters_clear(&mut self) {
+        if self.access_counters_enabled() {
+            self.mem.set_access_counters_enabled(true);
+        }
+    }
+
+    /// Times `addr` was accessed as `kind`, 0 if the accesses aren't counted.
+    pub fn access_count_at(&self, kind: AccessKind, addr: u16) -> u32 {
+        self.mem.access_counters().map_or(0, |counters| counters.count_at(kind, addr))
+    }
+
+    /// The `kind` access counts of `len` addresses from `start` (stopping at 0xFFFF), None if the
+    /// accesses aren't counted.
+    pub fn access_counts(&self, kind: AccessKind, start: u16, len: u32) -> Option<Vec<u32>> {
+        self.mem.access_counters().map(|counters| counters.range(kind, start, len))
+    }
+
+    /// Like `Self::access_counts`, adding every kind of access together (for heatmaps).
+    pub fn access_total_counts(&self, start: u16, len: u32) -> Option<Vec<u32>> {
+        self.mem.access_counters().map(|counters| counters.total_range(start, len))
+    }
+
+    /// Starts (forgetting the previous writes) or stops recording which instruction wrote every
+    /// address and in which cycle, keeping the last `history_len` writes (at least 1) of each one.
+    /// Only the writes of the cpu are recorded, and they are kept across resets.
+    pub fn set_write_provenance_enabled(&mut self, enabled: bool, history_len: u32) {
+        let history_len = history_len.max(1) as usize;
+
+        if enabled && self.mem.write_provenance().is_some_and(|provenance| provenance.history_len() == history_len) {
+            return;
+        }
+
+        self.mem.set_write_provenance(if enabled { Some(WriteProvenance::new(history_len)) } else { None });
+    }
+
+    pub fn write_provenance_enabled(&self) -> bool {
+        self.mem.write_provenance().is_some()
+    }
+
+    /// Forgets all the recorded writes.
+    pub fn write_provenance_clear(&mut self) {
+        if let Some(history_len) = self.mem.write_provenance().map(|provenance| provenance.history_len()) {
+            self.mem.set_write_provenance(Some(WriteProvenance::new(history_len)));
+        }
+    }
+
+    /// The last write of the cpu to `addr` while recording (see `Self::set_write_provenance_enabled`),
+    /// None if it wasn't written or the writes aren't recorded.
+    pub fn last_write(&self, addr: u16) -> Option<WriteRecord> {
+        self.mem.write_provenance().and_then(|provenance| provenance.last_write(addr))
+    }
+
+    /// The recorded writes to `addr`, from the newest, empty if the writes aren't recorded.
+    pub fn write_history(&self, addr: u16) -> Vec<WriteRecord> {
+        self.mem.write_provenance().map_or_else(Vec::new, |provenance| provenance.history(addr))
+    }
+
+    /// Sets what the RAM devices contain after a reset (from the next one), `value` is the byte
+    /// for `RamInitKind::Pattern` and the seed for `RamInitKind::Random`. By default they are
+    /// zeroed, but real RAM powers on with garbage.
+    pub fn set_ram_init(&mut self, kind: RamInitKind, value: u32) {
+        self.ram_init = RamInit::from_kind(kind, value);
+
+        for index in 0..self.mem.devices().len() {
+            if let Some(ram) = self.mem.device_as_mut::<Ram>(index) {
+                ram.set_init(self.ram_init);
+            }
+        }
+    }
+
+    /// Starts or stops reporting the reads (and executions) of RAM that wasn't written since the
+    /// last reset, see `Self::take_uninit_reads`.
+    pub fn set_uninit_reads_enabled(&mut self, enabled: bool) {
+        if enabled != self.uninit_reads_enabled() {
+            self.mem.set_uninit_reads_enabled(enabled);
+        }
+    }
+
+    pub fn uninit_reads_enabled(&self) -> bool {
+        self.mem.uninit_reads().is_some()
+    }
+
+    /// Removes and returns the reads of uninitialized RAM since the last call, only the first one
+    /// of every address after a reset is reported.
+    pub fn take_uninit_reads(&mut self) -> Vec<UninitRead> {
+        self.mem.uninit_reads_mut().map_or_else(Vec::new, |uninit_reads| uninit_reads.take())
+    }
+
+    /// Returns the addresses in `instruction_addresses` that were never executed (see
+    /// `Self::set_access_counters_enabled`), all of them if the accesses aren't counted.
+    ///
+    /// The addresses of an assembled program come from `Assembler::instruction_addresses`.
+    pub fn coverage_unexecuted(&self, instruction_addresses: &[u16]) -> Vec<u16> {
+        instruction_addresses.iter()
+            .copied()
+            .filter(|addr| self.access_count_at(AccessKind::Execute, *addr) == 0)
+            .collect()
+    }
+
+    /// Sets (or replaces, with its hits at 0) a breakpoint that stops running (see `Self::run`)
+    /// when the pc gets to `addr`.
+    ///
+    /// - `condition`: an expression (like `A == $FF && mem[$10] > 3`), the breakpoint is only hit
+    ///   when it isn't 0. Empty for none.
+    /// - `hit_count`: it only fires from this hit on, 0 or 1 for every hit.
+    /// - `log_message`: makes it a logpoint, that instead of stopping adds the message to the log
+    ///   (see `Self::take_breakpoint_log`). `{expr}` is replaced by the value of the expression,
+    ///   `{expr:x}` in hex and `{expr:b}` in binary. Empty for a normal breakpoint.
+    ///
+    /// The expressions have numbers (`255`, `$FF`, `0xFF`, `%11111111`), the registers (`A`, `X`,
+    /// `Y`, `S`, `P`, `PC`), the flags (`C`, `Z`, `I`, `D`, `V`, `N`), memory (`mem[addr]`, and
+    /// `word[addr]` for 16 bits) and the C operators.
+    pub fn set_breakpoint(&mut self, addr: u16, condition: &str, hit_count: u32, log_message: &str) -> Result<(), SystemError> {
+        self.breakpoints.set(addr, condition, hit_count, log_message)
+            .map_err(|(expr, reason)| SystemError::InvalidExpression { expr, reason })
+    }
+
+    /// Returns true if there was a breakpoint at `addr`.
+    pub fn remove_breakpoint(&mut self, addr: u16) -> bool {
+        self.breakpoints.remove(addr)
+    }
+
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    /// The addresses with a breakpoint, in order.
+    pub fn breakpoint_addresses(&self) -> Vec<u16> {
+        self.breakpoints.addresses()
+    }
+
+    /// Times the breakpoint at `addr` was hit (with its condition true) since it was set or the
+    /// last reset, None if there isn't one.
+    pub fn breakpoint_hits(&self, addr: u16) -> Option<u32> {
+        self.breakpoints.hits(addr)
+    }
+
+    /// Removes and returns the messages of the logpoints since the last call, every line starts
+    /// with the address of the logpoint.
+    pub fn take_breakpoint_log(&mut self) -> Vec<String> {
+        self.breakpoints.take_log()
+    }
+
+    /// The current value of an expression, with the syntax of the breakpoint conditions (see
+    /// `Self::set_breakpoint`).
+    pub fn evaluate(&self, expr: &str) -> Result<f64, SystemError> {
+        let value = Expr::parse(expr)
+            .map_err(|reason| SystemError::InvalidExpression { expr: expr.to_string(), reason })?
+            .eval(&self.cpu, &self.mem);
+
+        Ok(value as f64)
+    }
+
+    /// The call stack as text for backtraces, the pc first and then one line per frame, from the
+    /// innermost to the outermost (the called address, the kind of call and where it came from).
+    pub fn call_stack_backtrace(&self) -> String {
+        let mut text = format!("#0 ${:04X}\n", self.cpu.register(CpuRegister::Pc));
+
+        for (depth, frame) in self.call_stack.frames().iter().rev().enumerate() {
+            let kind = match frame.kind {
+                FrameKind::Jsr => "JSR",
+                FrameKind::Brk => "BRK",
+                FrameKind::Irq => "IRQ",
+                FrameKind::Nmi => "NMI",
+            };
+
+            text += &format!("#{} ${:04X} {} from ${:04X} (S=${:02X})\n", depth + 1, frame.target, kind, frame.caller_pc, frame.sp);
+        }
+
+        text
     }
 
     /// Resets the system, clearing all non-persistent data containers.
     pub fn reset_system(&mut self) {
         self.cpu.reset_system();
+        self.cycles = 0;
+        self.clear_call_stack();
+        self.breakpoints.reset_hits();
 
         self.mem.reset_bus();
         self.mem.reset_devices();
@@ -57,42 +500,102 @@ impl System {
     /// Resets the system, clearing all data containers, including persistent ones like the rom.
     pub fn reset_hard(&mut self) {
         self.cpu.reset_hard();
+        self.cycles = 0;
+        self.clear_call_stack();
+        self.breakpoints.reset_hits();
 
         self.mem.reset_bus();
         self.mem.reset_devices_hard();
     }
 
-    pub fn add_device_with_uid(&mut self, device: DeviceId, start: u16, size: u16, uid: u16) -> bool {
-        let actual_device_size = DeviceId::fixed_size(&device)
-            .unwrap_or(size);
+    /// Adds a device of type `device`, if it has a fixed size, `size` must be a 0.
+    ///
+    /// Devices can overlap, the first one added gets the shared addresses (see `Self::swap_devices_by_index`).
+    pub fn add_device_with_uid(&mut self, device: DeviceId, start: u16, size: u16, uid: u16) -> Result<(), SystemError> {
+        let dev = DeviceFactory::with_size(device, size)?;
 
-        if (std::u16::MAX - actual_device_size) >= start { //check for overflows
-            let result = DeviceFactory::with_size(device, size);
+        self.add_device_checked(dev, start, uid)
+    }
 
-            match result {
-                Ok(dev) => {
-                    let end = start + dev.size();
+    /// Adds a device of the type registered as `name`, see `dev::DeviceRegistry`.
+    ///
+    /// If the device has a fixed size, `size` must be a 0.
+    pub fn add_device_by_name(&mut self, name: &str, start: u16, size: u16, uid: u16) -> Result<(), SystemError> {
+        let dev = self.registry.create(name, size)?;
 
-                    self.mem.add_device_unchecked_range(dev, start, end, uid);
+        self.add_device_checked(dev, start, uid)
+    }
 
-                    true
-                }
+    pub fn set_initial_pc(&mut self, value: u16) {
+        self.cpu.set_initial_pc(value);
+    }
 
-                Err(_) => {
-                    // for now the only possible error (in the factory) is an invalid size,
-                    // in the future we might want to be more explicit.
-                    false
-                }
+    pub fn cpu_register(&self, reg: CpuRegister) -> u16 {
+        self.cpu.register(reg)
+    }
+
+    /// Changes a cpu register, the new pc is where the next instruction is fetched from.
+    ///
+    /// Returns false (and does nothing) if the cpu is in the middle of an instruction (finish it with
+    /// `Self::execute_operation` first) or if `value` doesn't fit in the register.
+    pub fn cpu_set_register(&mut self, reg: CpuRegister, value: u16) -> bool {
+        self.cpu.set_register(reg, value)
+    }
+
+    pub fn cpu_flag(&self, flag: CpuFlag) -> bool {
+        self.cpu.flag(flag)
+    }
+
+    /// Sets or clears a flag of the cpu P register, see `Self::cpu_set_register`.
+    pub fn cpu_set_flag(&mut self, flag: CpuFlag, value: bool) -> bool {
+        self.cpu.set_flag(flag, value)
+    }
+
+    /// Makes the cpu start at the address in the reset vector ($FFFC), read on every reset.
+    pub fn set_initial_pc_from_reset_vector(&mut self) {
+        self.cpu.set_initial_pc_from_reset_vector();
+    }
+
+    /// Replaces the whole machine with the one described in the JSON `text` (see `system::MachineConfig`).
+    ///
+    /// The contents files are taken from `files`, which maps the file names to Uint8Arrays.
+    /// Throws an Error with the reason if the configuration is invalid, leaving the machine untouched.
+    pub fn load_config(&mut self, text: &str, files: &Map) -> Result<(), JsValue> {
+        let read_file = |path: &str| {
+            let data = files.get(&JsValue::from_str(path));
+
+            if data.is_undefined() {
+                None
+            } else {
+                Some(Uint8Array::new(&data).to_vec())
             }
-        } else {
-            // the range should have already been validated by the client,
-            // this wont be reached if properly implemented.
-            false
-        }
+        };
+
+        MachineConfig::from_json(text)
+            .and_then(|config| self.load_machine_config(&config, &read_file))
+            .map_err(|e| js_sys::Error::new(&e.to_string()).into())
     }
 
-    pub fn set_initial_pc(&mut self, value: u16) {
-        self.cpu.set_initial_pc(value);
+    /// Replaces the whole machine with the built in profile `name`, see `Self::profile_names`.
+    ///
+    /// Throws an Error if there is no such profile.
+    pub fn load_profile(&mut self, name: &str) -> Result<(), JsValue> {
+        MachineProfile::by_name(name)
+            .ok_or_else(|| ConfigError::new(format!("unknown profile \"{}\"", name)))
+            .and_then(|profile| self.load_machine_config(&profile.config(), &|_| None))
+            .map_err(|e| js_sys::Error::new(&e.to_string()).into())
+    }
+
+    /// Names of the built in profiles, as an Array of strings.
+    pub fn profile_names() -> Array {
+        MachineProfile::ALL.iter()
+            .map(|profile| JsValue::from_str(profile.name()))
+            .collect()
+    }
+
+    /// Returns the current machine as JSON, see `Self::load_config`.
+    pub fn export_config(&self) -> String {
+        self.export_machine_config().to_json()
     }
 
     //
@@ -105,21 +608,110 @@ impl System {
     //
 
 
-    pub fn remove_device_by_index(&mut self, index: usize) -> bool {
-        self.mem.remove_device_by(index - 1)
+    /// Removes the device [Index] and its wires, it can be done at any time, even in the middle of
+    /// a program.
+    pub fn remove_device_by_index(&mut self, index: usize) -> Result<(), SystemError> {
+        self.check_device_index(index)?;
+
+        self.mem.remove_device_by(index - 1);
+
+        Ok(())
     }
 
-    pub fn swap_devices_by_index(&mut self, index_a: usize, index_b: usize) -> bool {
-        let a_is_valid = index_a > 0 && index_a <= self.mem.devices().len();
-        let b_is_valid = index_b > 0 && index_b <= self.mem.devices().len();
+    /// Maps the device [Index] starting at `start`, keeping its contents, its mirror and its place
+    /// in the device list (so it keeps its priority over overlapping devices).
+    pub fn move_device_by_index(&mut self, index: usize, start: u16) -> Result<(), SystemError> {
+        self.check_device_index(index)?;
+
+        let holder = &mut self.mem.devices_mut()[index - 1];
+        let mapped_size = holder.range().end - holder.range().start;
+        let end = start as u32 + mapped_size;
 
-        if a_is_valid && b_is_valid {
-            self.mem.devices_mut().swap(index_a - 1, index_b - 1);
+        if end > ADDRESS_SPACE_SIZE {
+            return Err(SystemError::RangeOverflow {
+                start,
+                size: mapped_size,
+            });
+        }
+
+        holder.set_range(start, end);
 
-            true
+        Ok(())
+    }
+
+    /// Replaces the device [Index] with a new one of the same type and `size`, copying the contents
+    /// that still fit. Only memory devices (RAM and ROM) can be resized, as only their contents
+    /// would be kept.
+    ///
+    /// If the device is mirrored, the mirror is kept as long as the new size fits in it.
+    pub fn resize_device_by_index(&mut self, index: usize, size: u16) -> Result<(), SystemError> {
+        self.check_device_index(index)?;
+
+        let holder = &self.mem.devices()[index - 1];
+        let type_name = holder.device().type_name().to_string();
+
+        let has_fixed_size = self.registry.by_name(&type_name)
+            .is_some_and(|dev_type| matches!(dev_type.size(), DeviceSize::Fixed(_)));
+
+        if has_fixed_size {
+            return Err(SystemError::InvalidSize {
+                type_name,
+                size,
+            });
+        }
+
+        if !matches!(holder.device().device_id(), DeviceId::Ram | DeviceId::Rom) {
+            return Err(SystemError::NotResizable(type_name));
+        }
+
+        let mut new_dev = self.registry.create(&type_name, size)?;
+        self.apply_ram_init(&mut new_dev);
+
+        let start = holder.range().start as u16;
+        let old_size = holder.device().size();
+        let mapped_size = holder.range().end - holder.range().start;
+
+        let new_mapped_size = if mapped_size != old_size as u32 && size as u32 <= mapped_size {
+            mapped_size
+        } else {
+            size as u32
+        };
+
+        if start as u32 + new_mapped_size > ADDRESS_SPACE_SIZE {
+            return Err(SystemError::RangeOverflow {
+                start,
+                size: new_mapped_size,
+            });
+        }
+
+        let new_ram: &mut dyn Any = new_dev.as_mut();
+        let old_ram: &dyn Any = holder.device().as_ref();
+
+        // pokes would mark all the RAM as written, see `Self::take_uninit_reads`
+        if let (Some(new_ram), Some(old_ram)) = (new_ram.downcast_mut::<Ram>(), old_ram.downcast_ref::<Ram>()) {
+            new_ram.copy_contents_from(old_ram);
         } else {
-            false
+            for offset in 0..old_size.min(size) {
+                new_dev.poke_unchecked(offset, holder.device().peek_unchecked(offset));
+            }
         }
+
+        let holder = &mut self.mem.devices_mut()[index - 1];
+        holder.replace_device(new_dev);
+        holder.set_range(start, start as u32 + new_mapped_size);
+
+        Ok(())
+    }
+
+    /// Swaps the position of two devices, when they overlap, the device with the lowest index
+    /// gets the shared addresses.
+    pub fn swap_devices_by_index(&mut self, index_a: usize, index_b: usize) -> Result<(), SystemError> {
+        self.check_device_index(index_a)?;
+        self.check_device_index(index_b)?;
+
+        self.mem.devices_mut().swap(index_a - 1, index_b - 1);
+
+        Ok(())
     }
 
     // we cant (yet?) send a Vec/n size array (at least not without using serde and its huge dependencies),
@@ -137,10 +729,84 @@ impl System {
         }
     }
 
-    /// WARNING: Using raw pointers might cause system instability,
-    /// make sure you know what you're doing.
-    pub fn device_data_ptr_by_index(&mut self, index: usize) -> Option<usize> {
-        self.mem.device_data_ptr(index - 1)
+    /// Reads the value at `addr` through the address map, without side effects on the devices,
+    /// the bus or the cycle count. Unmapped addresses read as 0.
+    pub fn peek(&self, addr: u16) -> u8 {
+        self.mem.peek(addr)
+    }
+
+    /// Writes `value` at `addr` through the address map, without side effects on the bus or the cycle
+    /// count. Unlike the cpu, it can write to ROMs.
+    ///
+    /// Returns false if the address is not mapped or its device can't be poked (most I/O devices).
+    pub fn poke(&mut self, addr: u16, value: u8) -> bool {
+        self.mem.poke(addr, value)
+    }
+
+    /// Pokes `data` starting at `addr`, it can span multiple devices.
+    ///
+    /// Returns false if the data doesn't fit in the address space (nothing is written then) or if
+    /// any address can't be poked (the others are still written).
+    pub fn load(&mut self, addr: u16, data: &[u8]) -> bool {
+        if addr as u32 + data.len() as u32 > ADDRESS_SPACE_SIZE {
+            return false;
+        }
+
+        data.iter()
+            .enumerate()
+            .fold(true, |all_mapped, (i, value)| self.mem.poke(addr + i as u16, *value) && all_mapped)
+    }
+
+    /// Peeks the addresses from `start` to `end` (exclusive), `end` is clamped to the address space.
+    pub fn dump(&self, start: u16, end: u32) -> Vec<u8> {
+        (start as u32..end.min(ADDRESS_SPACE_SIZE))
+            .map(|addr| self.mem.peek(addr as u16))
+            .collect()
+    }
+
+    /// Loads a memory image in `format` (see `system::ImageFormat`).
+    ///
+    /// `address` and `skip` are only used by raw images: the file is loaded at `address`, ignoring its
+    /// first `skip` bytes. Throws an Error with the reason (and the line, for text formats) if the file
+    /// is invalid or writes to unmapped addresses, without loading anything.
+    pub fn load_image(&mut self, format: ImageFormat, data: &[u8], address: u16, skip: usize) -> Result<(), JsValue> {
+        Image::parse(format, data, address, skip)
+            .and_then(|image| self.load_image_data(&image))
+            .map_err(|e| js_sys::Error::new(&e.to_string()).into())
+    }
+
+    /// Copies `len` bytes of the device [Index] starting at `offset`, without side effects (see `Self::peek`).
+    ///
+    /// The range is clamped to the device size, which can be smaller than its mapped range if it's mirrored.
+    pub fn device_copy_range_by_index(&self, index: usize, offset: u16, len: u32) -> Option<Vec<u8>> {
+        self.mem.devices()
+            .get(index.wrapping_sub(1))
+            .map(|holder| {
+                let dev = holder.device();
+                let end = (offset as u32 + len).min(dev.size() as u32);
+
+                (offset as u32..end)
+                    .map(|offset| dev.peek_unchecked(offset as u16))
+                    .collect()
+            })
+    }
+
+    /// Copies the whole device [Index], see `Self::device_copy_range_by_index`.
+    pub fn device_snapshot_by_index(&self, index: usize) -> Option<Vec<u8>> {
+        self.device_copy_range_by_index(index, 0, ADDRESS_SPACE_SIZE)
+    }
+
+    /// Returns the pages of the device [Index] that changed since the last call, so memory views
+    /// only need to copy those (see `Self::memory_page_size`). Every page is dirty on the first call.
+    pub fn device_take_dirty_pages_by_index(&mut self, index: usize) -> Option<Vec<u16>> {
+        self.mem.devices_mut()
+            .get_mut(index.wrapping_sub(1))
+            .map(|holder| holder.device_mut().take_dirty_pages())
+    }
+
+    /// Size in bytes of the pages returned by `Self::device_take_dirty_pages_by_index`.
+    pub fn memory_page_size() -> u16 {
+        PAGE_SIZE
     }
 
     pub fn device_widget_update_by_index(&mut self, index: usize, pkg: &Map) {
@@ -161,4 +827,597 @@ impl System {
                 .and_then(|dev| Some(dev.device_mut().setup_widget(pkg)));
         }
     }
+
+    /// Returns the value of the pins of `port` of device [Index], or a None/null if
+    /// the device doesn't have such port.
+    pub fn device_port_output_by_index(&self, index: usize, port: u8) -> Option<u8> {
+        self.mem.devices()
+            .get(index - 1)
+            .and_then(|dev| dev.device().port_output(port))
+    }
+
+    /// Drives the pins of `port` of device [Index] from the host.
+    pub fn device_set_port_input_by_index(&mut self, index: usize, port: u8, value: u8) -> bool {
+        self.mem.devices_mut()
+            .get_mut(index - 1)
+            .map(|dev| dev.device_mut().set_port_input(port, value))
+            .is_some()
+    }
+
+    /// Removes and returns up to `max` samples from the sound device [Index],
+    /// or a None/null if it isn't a sound device.
+    pub fn sound_pull_samples_by_index(&mut self, index: usize, max: usize) -> Option<Vec<f32>> {
+        self.mem.device_as_mut::<SoundGenerator>(index - 1)
+            .map(|dev| dev.pull_samples(max))
+    }
+
+    /// Returns the samples buffered in the sound device [Index] as a WAV file,
+    /// or a None/null if it isn't a sound device.
+    pub fn sound_export_wav_by_index(&self, index: usize) -> Option<Vec<u8>> {
+        self.mem.device_as::<SoundGenerator>(index - 1)
+            .map(|dev| dev.export_wav())
+    }
+
+    pub fn sound_set_sample_rate_by_index(&mut self, index: usize, sample_rate: u32) -> bool {
+        self.mem.device_as_mut::<SoundGenerator>(index - 1)
+            .map(|dev| dev.set_sample_rate(sample_rate))
+            .is_some()
+    }
+
+    /// Queues `data` to be received by the serial device [Index].
+    ///
+    /// Returns false if the device isn't a serial device using the default (buffer) backend.
+    pub fn acia_push_input_by_index(&mut self, index: usize, data: &[u8]) -> bool {
+        self.mem.device_as_mut::<Acia>(index - 1)
+            .and_then(|dev| dev.backend_as_mut::<BufferSerial>())
+            .map(|backend| backend.push_input(data))
+            .is_some()
+    }
+
+    /// Removes and returns everything transmitted by the serial device [Index],
+    /// or a None/null if the device isn't a serial device using the default (buffer) backend.
+    pub fn acia_take_output_by_index(&mut self, index: usize) -> Option<Vec<u8>> {
+        self.mem.device_as_mut::<Acia>(index - 1)
+            .and_then(|dev| dev.backend_as_mut::<BufferSerial>())
+            .map(|backend| backend.take_output())
+    }
+
+    /// Seeds the random number device [Index], making its output deterministic.
+    pub fn prng_seed_by_index(&mut self, index: usize, seed: u32) -> bool {
+        self.mem.device_as_mut::<Prng>(index - 1)
+            .map(|dev| dev.seed(seed))
+            .is_some()
+    }
+
+    /// Makes the clock device [Index] start at `unix_seconds` and advance with the system ticks,
+    /// instead of following the host time.
+    pub fn rtc_set_time_by_index(&mut self, index: usize, unix_seconds: f64) -> bool {
+        self.mem.device_as_mut::<RealTimeClock>(index - 1)
+            .map(|dev| dev.set_time(unix_seconds as u64))
+            .is_some()
+    }
+
+    pub fn rtc_use_host_time_by_index(&mut self, index: usize) -> bool {
+        self.mem.device_as_mut::<RealTimeClock>(index - 1)
+            .map(|dev| dev.use_host_time())
+            .is_some()
+    }
+
+    /// Holds (or releases) `line` from the host, as if it was asserted by a device on every cycle.
+    pub fn set_signal(&mut self, line: SignalLine, asserted: bool) {
+        self.mem.signals_mut().set_held_by_host(line, asserted);
+    }
+
+    /// Returns true if `line` was asserted in the last cycle (or is held by the host).
+    pub fn signal_is_asserted(&self, line: SignalLine) -> bool {
+        self.mem.signals().is_asserted(line)
+    }
+
+    /// Connects the output of port `from_port` of device `from_uid` to the input of port `to_port`
+    /// of device `to_uid`, the value is copied on every cycle.
+    ///
+    /// Returns false if any of the devices doesn't exist or they are already connected.
+    pub fn connect_ports(&mut self, from_uid: u16, from_port: u8, to_uid: u16, to_port: u8) -> bool {
+        self.mem.add_wire(PortWire {
+            from_uid,
+            from_port,
+
+            to_uid,
+            to_port,
+        })
+    }
+
+    pub fn disconnect_ports(&mut self, from_uid: u16, from_port: u8, to_uid: u16, to_port: u8) -> bool {
+        self.mem.remove_wire(PortWire {
+            from_uid,
+            from_port,
+
+            to_uid,
+            to_port,
+        })
+    }
+
+    /// Returns the text visible on the character LCD [Index], one line per row.
+    pub fn lcd_text_by_index(&mut self, index: usize) -> Option<String> {
+        self.mem.device_as_mut::<CharLcd>(index - 1)
+            .map(|dev| dev.text())
+    }
+
+    /// Presses or releases the key at `row`, `column` of the keyboard matrix device [Index].
+    pub fn keyboard_set_key_by_index(&mut self, index: usize, row: u8, column: u8, pressed: bool) -> bool {
+        self.mem.device_as_mut::<KeyboardMatrix>(index - 1)
+            .map(|dev| dev.set_key(row, column, pressed))
+            .is_some()
+    }
+
+    pub fn keyboard_release_all_by_index(&mut self, index: usize) -> bool {
+        self.mem.device_as_mut::<KeyboardMatrix>(index - 1)
+            .map(|dev| dev.release_all())
+            .is_some()
+    }
+
+    /// Replaces the state of all the buttons of the joystick device [Index], a bit per button.
+    pub fn joystick_set_buttons_by_index(&mut self, index: usize, buttons: u8) -> bool {
+        self.mem.device_as_mut::<Joystick>(index - 1)
+            .map(|dev| dev.set_buttons(buttons))
+            .is_some()
+    }
+
+    /// Sets the last key pressed of the key latch device [Index].
+    pub fn key_latch_set_by_index(&mut self, index: usize, key: u8) -> bool {
+        self.mem.device_as_mut::<KeyLatch>(index - 1)
+            .map(|dev| dev.set_key(key))
+            .is_some()
+    }
+
+    /// Queues keys typed on the keyboard of the PIA device [Index].
+    pub fn pia_push_input_by_index(&mut self, index: usize, data: &[u8]) -> bool {
+        self.mem.device_as_mut::<Pia>(index - 1)
+            .map(|dev| dev.push_input(data))
+            .is_some()
+    }
+
+    /// Removes and returns everything sent to the display of the PIA device [Index],
+    /// or a None/null if the device isn't a PIA.
+    pub fn pia_take_output_by_index(&mut self, index: usize) -> Option<Vec<u8>> {
+        self.mem.device_as_mut::<Pia>(index - 1)
+            .map(|dev| dev.take_output())
+    }
+
+    /// Removes and returns everything printed by the semihosting device [Index],
+    /// or a None/null if the device isn't one.
+    pub fn semihost_take_output_by_index(&mut self, index: usize) -> Option<Vec<u8>> {
+        self.mem.device_as_mut::<Semihost>(index - 1)
+            .map(|dev| dev.take_output())
+    }
+
+    /// Adds (or replaces) the file `name` of the semihosting device [Index].
+    pub fn semihost_set_file_by_index(&mut self, index: usize, name: &str, data: &[u8]) -> bool {
+        self.mem.device_as_mut::<Semihost>(index - 1)
+            .map(|dev| dev.set_file(name, data))
+            .is_some()
+    }
+
+    /// Returns the file `name` of the semihosting device [Index], or a None/null if it doesn't exist.
+    pub fn semihost_file_by_index(&self, index: usize, name: &str) -> Option<Vec<u8>> {
+        self.mem.device_as::<Semihost>(index - 1)
+            .and_then(|dev| dev.file(name).map(|data| data.to_vec()))
+    }
+
+    /// Presses or releases the buttons in `mask` of the joystick device [Index].
+    pub fn joystick_set_button_by_index(&mut self, index: usize, mask: u8, pressed: bool) -> bool {
+        self.mem.device_as_mut::<Joystick>(index - 1)
+            .map(|dev| dev.set_button(mask, pressed))
+            .is_some()
+    }
+}
+
+// utils
+impl System {
+    fn clear_call_stack(&mut self) {
+        self.call_stack.clear();
+
+        if let Some(profiler) = &mut self.profiler {
+            profiler.reset_call_stack(&[]);
+        }
+    }
+
+    fn new_profiler(&self) -> Profiler {
+        let mut profiler = Profiler::new();
+        profiler.reset_call_stack(self.call_stack.frames());
+
+        profiler
+    }
+
+    /// Implements `Self::run`, also stopping when `at_target` returns true between instructions,
+    /// after at least one instruction. `at_target` is called between every instruction.
+    fn run_until(&mut self, max_cycles: u32, mut at_target: impl FnMut(&System) -> bool) -> RunResult {
+        let mut cycles = 0;
+
+        // writes from before the run don't stop it
+        self.mem.take_exit_code();
+
+        loop {
+            // the instruction the run starts at is always executed, so running or stepping again
+            // continues past the one that stopped the previous run
+            if self.cpu.is_between_instructions() && cycles > 0 {
+                if at_target(self) {
+                    return RunResult {
+                        reason: StopReason::TargetReached,
+                        exit_code: 0,
+                        cycles,
+                    };
+                }
+
+                if self.breakpoints.check(&self.cpu, &self.mem) {
+                    return RunResult {
+                        reason: StopReason::Breakpoint,
+                        exit_code: 0,
+                        cycles,
+                    };
+                }
+
+                if let Some(reason) = self.halt_reason() {
+                    return RunResult {
+                        reason,
+                        exit_code: self.cpu.register(CpuRegister::A) as u8,
+                        cycles,
+                    };
+                }
+            }
+
+            if cycles == max_cycles {
+                return RunResult {
+                    reason: StopReason::CycleLimit,
+                    exit_code: 0,
+                    cycles,
+                };
+            }
+
+            self.tick();
+            cycles += 1;
+
+            if let Some(mut exit_code) = self.mem.take_exit_code() {
+                // read-modify-write instructions write twice, the last value is the one that counts
+                while !self.cpu.operation_is_done() {
+                    self.tick();
+                    cycles += 1;
+
+                    exit_code = self.mem.take_exit_code().unwrap_or(exit_code);
+                }
+
+                return RunResult {
+                    reason: StopReason::ExitPort,
+                    exit_code,
+                    cycles,
+                };
+            }
+        }
+    }
+
+    fn halt_reason(&self) -> Option<StopReason> {
+        self.halt_conditions.check(
+            self.cpu.register(CpuRegister::Pc),
+            |addr| self.mem.peek(addr),
+            |flag| self.cpu.flag(flag),
+        )
+    }
+
+    fn add_device_checked(&mut self, mut dev: BoxedDev, start: u16, uid: u16) -> Result<(), SystemError> {
+        let end = start as u32 + dev.size() as u32;
+
+        if end > ADDRESS_SPACE_SIZE {
+            return Err(SystemError::RangeOverflow {
+                start,
+                size: dev.size() as u32,
+            });
+        }
+
+        if self.mem.device_index_by_uid(uid).is_some() {
+            return Err(SystemError::DuplicatedUid(uid));
+        }
+
+        self.connect_device(&mut dev);
+        self.mem.add_device_unchecked_range(dev, start, end, uid);
+
+        Ok(())
+    }
+
+    fn symbol_name(symbols: &Map, addr: u16) -> String {
+        symbols.get(&JsValue::from(addr)).as_string()
+            .unwrap_or_else(|| address_name(addr))
+    }
+
+    /// Gives a new device what it needs from the system: the contents set with `Self::set_ram_init`
+    /// if it's RAM, and the cycle count if it's a semihosting device.
+    fn connect_device(&self, dev: &mut BoxedDev) {
+        self.apply_ram_init(dev);
+
+        let dev: &mut dyn Any = dev.as_mut();
+
+        if let Some(semihost) = dev.downcast_mut::<Semihost>() {
+            semihost.set_clock(Rc::clone(&self.clock));
+        }
+    }
+
+    /// Gives a new device the contents set with `Self::set_ram_init`, if it's RAM.
+    fn apply_ram_init(&self, dev: &mut BoxedDev) {
+        let dev: &mut dyn Any = dev.as_mut();
+
+        if let Some(ram) = dev.downcast_mut::<Ram>() {
+            ram.set_init(self.ram_init);
+            ram.reset_system();
+        }
+    }
+
+    /// Checks that `index` is a device other than the CPU.
+    fn check_device_index(&self, index: usize) -> Result<(), SystemError> {
+        if index == 0 {
+            Err(SystemError::CpuNotRemovable)
+        } else if index > self.mem.devices().len() {
+            Err(SystemError::BadIndex(index))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+// Rust only api
+impl System {
+    /// Replaces the whole machine with the one described in `config`, `read_file` returns the
+    /// contents files by name.
+    ///
+    /// Nothing is changed if the configuration is invalid.
+    pub fn load_machine_config(
+        &mut self,
+        config: &MachineConfig,
+        read_file: &dyn Fn(&str) -> Option<Vec<u8>>,
+    ) -> Result<(), ConfigError> {
+        let error = |path: String, message: &str| ConfigError::new(format!("{}: {}", path, message));
+
+        let CpuVariant::Nmos6502 = config.cpu_variant;
+
+        // everything is validated before touching the current machine
+        let mut devices = Vec::new();
+
+        for (i, dev_config) in config.devices.iter().enumerate() {
+            let path = format!("devices[{}]", i);
+
+            if config.devices[..i].iter().any(|other| other.uid == dev_config.uid) {
+                return Err(error(path, &SystemError::DuplicatedUid(dev_config.uid).to_string()));
+            }
+
+            let dev = self.registry.create(&dev_config.type_name, dev_config.size)
+                .map_err(|e| error(path.clone(), &e.to_string()))?;
+
+            let mapped_size = dev_config.mirror.unwrap_or(dev.size() as u32);
+            let end = dev_config.start as u32 + mapped_size;
+
+            if mapped_size < dev.size() as u32 {
+                return Err(error(path, "the mirror is smaller than the device"));
+            }
+
+            if end > ADDRESS_SPACE_SIZE {
+                let e = SystemError::RangeOverflow {
+                    start: dev_config.start,
+                    size: mapped_size,
+                };
+
+                return Err(error(path, &e.to_string()));
+            }
+
+            // unlike the devices added one by one, overlapping devices are most likely a mistake here
+            let overlapping = devices.iter()
+                .find(|(_, other, other_end, _): &&(_, &DeviceConfig, u32, _)| {
+                    (dev_config.start as u32) < *other_end && (other.start as u32) < end
+                });
+
+            if let Some((_, other, _, _)) = overlapping {
+                let e = SystemError::RangeOverlap {
+                    start: dev_config.start,
+                    end,
+                    other_uid: other.uid,
+                };
+
+                return Err(error(path, &e.to_string()));
+            }
+
+            let contents = match &dev_config.contents {
+                Some(contents) => {
+                    let data = match &contents.source {
+                        ContentsSource::File(file) => read_file(file)
+                            .ok_or_else(|| error(path.clone(), &format!("can't read the contents file \"{}\"", file)))?,
+
+                        ContentsSource::Data(data) => data.clone(),
+                    };
+
+                    if contents.offset as usize + data.len() > dev.size() as usize {
+                        return Err(error(path, "the contents don't fit in the device"));
+                    }
+
+                    Some((contents.offset, data))
+                }
+
+                None => None,
+            };
+
+            devices.push((dev, dev_config, end, contents));
+        }
+
+        for (i, wire) in config.wires.iter().enumerate() {
+            let exists = |uid| config.devices.iter().any(|dev| dev.uid == uid);
+
+            if !exists(wire.from_uid) || !exists(wire.to_uid) {
+                return Err(error(format!("wires[{}]", i), "unknown device uid"));
+            }
+        }
+
+        self.mem.clear();
+
+        match config.start_pc {
+            StartPc::Address(addr) => self.cpu.set_initial_pc(addr),
+            StartPc::ResetVector => self.cpu.set_initial_pc_from_reset_vector(),
+        }
+
+        let mut all_contents = Vec::new();
+
+        for (index, (mut dev, dev_config, end, contents)) in devices.into_iter().enumerate() {
+            self.connect_device(&mut dev);
+            self.mem.add_device_unchecked_range(dev, dev_config.start, end, dev_config.uid);
+
+            if let Some(contents) = contents {
+                all_contents.push((index, contents));
+            }
+        }
+
+        for wire in &config.wires {
+            self.mem.add_wire(*wire);
+        }
+
+        // the reset clears the memory, so the contents are loaded afterwards
+        self.reset_hard();
+
+        for (index, (offset, data)) in all_contents {
+            let dev = self.mem.devices_mut()[index].device_mut();
+
+            for (i, value) in data.iter().enumerate() {
+                dev.poke_unchecked(offset + i as u16, *value);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the whole machine with a built in profile.
+    pub fn load_machine_profile(&mut self, profile: MachineProfile) {
+        self.load_machine_config(&profile.config(), &|_| None)
+            .expect("the built in profiles are valid");
+    }
+
+    /// Loads every segment of `image`, see `Self::load_image`.
+    pub fn load_image_data(&mut self, image: &Image) -> Result<(), ImageError> {
+        for segment in &image.segments {
+            let start = segment.address as u32;
+
+            let unmapped = (start..start + segment.data.len() as u32)
+                .find(|addr| *addr >= ADDRESS_SPACE_SIZE || !self.mem.is_mapped(*addr as u16));
+
+            if let Some(addr) = unmapped {
+                let message = if addr >= ADDRESS_SPACE_SIZE {
+                    "the data doesn't fit in the address space".to_string()
+                } else {
+                    format!("the address ${:04X} is not mapped to any device", addr)
+                };
+
+                return Err(ImageError {
+                    line: None,
+                    message,
+                });
+            }
+        }
+
+        for segment in &image.segments {
+            self.load(segment.address, &segment.data);
+        }
+
+        Ok(())
+    }
+
+    /// Reads the configuration from the file at `path`, the contents files are relative to it.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_config_file(&mut self, path: &std::path::Path) -> Result<(), ConfigError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| ConfigError::new(format!("{}: {}", path.display(), e)))?;
+
+        let base_dir = path.parent().map(|dir| dir.to_path_buf()).unwrap_or_default();
+        let read_file = |file: &str| std::fs::read(base_dir.join(file)).ok();
+
+        let config = MachineConfig::from_json(&text)?;
+
+        self.load_machine_config(&config, &read_file)
+    }
+
+    /// Describes the current machine, see `Self::load_machine_config`. The contents of the memory
+    /// devices are the current ones, not the loaded ones.
+    pub fn export_machine_config(&self) -> MachineConfig {
+        let devices = self.mem.devices().iter()
+            .map(|holder| {
+                let dev = holder.device();
+
+                let mapped_size = holder.range().end - holder.range().start;
+                let has_fixed_size = self.registry.by_name(dev.type_name())
+                    .is_some_and(|dev_type| matches!(dev_type.size(), DeviceSize::Fixed(_)));
+
+                DeviceConfig {
+                    type_name: dev.type_name().to_string(),
+                    uid: holder.uid(),
+
+                    start: holder.range().start as u16,
+                    size: if has_fixed_size { 0 } else { dev.size() },
+
+                    mirror: Some(mapped_size).filter(|size| *size != dev.size() as u32),
+
+                    contents: Self::contents_snapshot(dev),
+                }
+            })
+            .collect();
+
+        MachineConfig {
+            cpu_variant: CpuVariant::Nmos6502,
+            start_pc: self.cpu.initial_pc().map_or(StartPc::ResetVector, StartPc::Address),
+
+            devices,
+            wires: self.mem.wires().to_vec(),
+        }
+    }
+
+    /// The current contents of a memory device, from the first to the last written byte of RAM or
+    /// the first to the last byte other than 0 of ROM. None for the other devices, their registers
+    /// aren't contents.
+    fn contents_snapshot(dev: &BoxedDev) -> Option<DeviceContents> {
+        let is_contents = |offset: u16| match dev.device_id() {
+            DeviceId::Ram => dev.is_initialized(offset),
+            DeviceId::Rom => dev.peek_unchecked(offset) != 0,
+            _ => false,
+        };
+
+        let first = (0..dev.size()).find(|offset| is_contents(*offset))?;
+        let last = (first..dev.size()).rev().find(|offset| is_contents(*offset))?;
+
+        Some(DeviceContents {
+            source: ContentsSource::Data((first..=last).map(|offset| dev.peek_unchecked(offset)).collect()),
+            offset: first,
+        })
+    }
+
+    /// Connections added with `Self::connect_ports`.
+    pub fn port_wires(&self) -> &[PortWire] {
+        self.mem.wires()
+    }
+
+    /// The profiler while it's enabled, to make reports with other subroutine names (see
+    /// `Profiler::report`).
+    pub fn profiler(&self) -> Option<&Profiler> {
+        self.profiler.as_ref()
+    }
+
+    /// Device types available to `Self::add_device_by_name`, new types can be registered here.
+    pub fn registry_mut(&mut self) -> &mut DeviceRegistry {
+        &mut self.registry
+    }
+
+    /// Connects the host side of the serial device [Index] to `backend`, see `dev::io::SerialBackend`.
+    pub fn acia_connect_by_index(&mut self, index: usize, backend: Box<dyn SerialBackend>) -> bool {
+        self.mem.device_as_mut::<Acia>(index - 1)
+            .map(|dev| dev.connect(backend))
+            .is_some()
+    }
+
+    /// Makes the semihosting device [Index] use the files in `dir` (or the ones in memory, if None).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn semihost_set_sandbox_by_index(&mut self, index: usize, dir: Option<std::path::PathBuf>) -> bool {
+        self.mem.device_as_mut::<Semihost>(index - 1)
+            .map(|dev| dev.set_sandbox(dir))
+            .is_some()
+    }
 }
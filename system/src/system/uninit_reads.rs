@@ -0,0 +1,74 @@
+use wasm_bindgen::prelude::*;
+
+use super::system::ADDRESS_SPACE_SIZE;
+
+/// Reads kept at most until taken, the oldest are dropped.
+const MAX_READS: usize = 256;
+
+/// A read of RAM that wasn't written since the last reset, see `UninitReads`.
+#[wasm_bindgen]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct UninitRead {
+    pub addr: u16,
+
+    /// Address of the instruction that read.
+    pub pc: u16,
+
+    cycle: u64,
+}
+
+#[wasm_bindgen]
+impl UninitRead {
+    /// The cycle (see `System::cycles`) of the read.
+    #[wasm_bindgen(getter)]
+    pub fn cycle(&self) -> f64 {
+        self.cycle as f64
+    }
+}
+
+/// Collects the reads of uninitialized RAM (see `AddressableDeviceTrait::is_initialized`), only
+/// the first one of every address until the next reset, so a loop doesn't flood them.
+pub struct UninitReads {
+    reads: Vec<UninitRead>,
+
+    reported: Vec<bool>,
+}
+
+impl Default for UninitReads {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UninitReads {
+    pub fn new() -> Self {
+        UninitReads {
+            reads: Vec::new(),
+
+            reported: vec![false; ADDRESS_SPACE_SIZE as usize],
+        }
+    }
+
+    /// Called on every reset, the memory is uninitialized again.
+    pub fn reset(&mut self) {
+        self.reported.fill(false);
+    }
+
+    pub fn report(&mut self, addr: u16, pc: u16, cycle: u64) {
+        if self.reported[addr as usize] {
+            return;
+        }
+
+        self.reported[addr as usize] = true;
+
+        if self.reads.len() == MAX_READS {
+            self.reads.remove(0);
+        }
+
+        self.reads.push(UninitRead { addr, pc, cycle });
+    }
+
+    pub fn take(&mut self) -> Vec<UninitRead> {
+        std::mem::take(&mut self.reads)
+    }
+}
@@ -0,0 +1,71 @@
+use std::collections::VecDeque;
+
+use wasm_bindgen::prelude::*;
+
+use super::system::ADDRESS_SPACE_SIZE;
+
+/// A write done by the cpu, see `WriteProvenance`.
+#[wasm_bindgen]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct WriteRecord {
+    /// Address of the instruction that wrote, or of the instruction interrupted by the IRQ or NMI
+    /// that pushed the value.
+    pub pc: u16,
+
+    pub value: u8,
+
+    cycle: u64,
+}
+
+#[wasm_bindgen]
+impl WriteRecord {
+    /// The cycle (see `System::cycles`) of the write.
+    #[wasm_bindgen(getter)]
+    pub fn cycle(&self) -> f64 {
+        self.cycle as f64
+    }
+}
+
+/// Remembers which instruction wrote every address and when, keeping the last `history_len`
+/// writes of each one.
+pub struct WriteProvenance {
+    /// The writes of every address, from the oldest.
+    writes: Vec<VecDeque<WriteRecord>>,
+
+    history_len: usize,
+}
+
+impl WriteProvenance {
+    /// `history_len` is the number of writes kept for every address, at least 1.
+    pub fn new(history_len: usize) -> Self {
+        WriteProvenance {
+            writes: vec![VecDeque::new(); ADDRESS_SPACE_SIZE as usize],
+
+            history_len: history_len.max(1),
+        }
+    }
+
+    pub fn history_len(&self) -> usize {
+        self.history_len
+    }
+
+    #[inline(always)]
+    pub fn record(&mut self, addr: u16, value: u8, pc: u16, cycle: u64) {
+        let writes = &mut self.writes[addr as usize];
+
+        if writes.len() == self.history_len {
+            writes.pop_front();
+        }
+
+        writes.push_back(WriteRecord { pc, value, cycle });
+    }
+
+    pub fn last_write(&self, addr: u16) -> Option<WriteRecord> {
+        self.writes[addr as usize].back().copied()
+    }
+
+    /// The writes kept for `addr`, from the newest.
+    pub fn history(&self, addr: u16) -> Vec<WriteRecord> {
+        self.writes[addr as usize].iter().rev().copied().collect()
+    }
+}